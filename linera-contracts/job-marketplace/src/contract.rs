@@ -12,15 +12,17 @@ Enhanced with:
 
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-mod state;
-
 use job_marketplace::{
-    AgentProfile, AgentRating, Bid, ChatMessage, Dispute, DisputeStatus, EscrowInfo,
-    EscrowStatus, Job, JobCategory, JobMarketplace, JobMarketplaceError, JobStatus,
-    Message, Milestone, MilestoneInput, MilestoneStatus, Operation, VerificationLevel,
+    AgentProfile, AgentRating, Bid, ChatMessage, ClientProfile, ClientRating, Dispute, DisputeStatus, EscrowInfo,
+    EscrowStatus, InstantiationArgument, Job, JobCategory, JobEvent, JobMarketplace,
+    JobMarketplaceError, JobStatus, MarketplaceParameters, Message, Milestone, MilestoneInput, MilestoneStatus, Operation,
+    OperationResponse,
+    RemoteJob,
+    VerificationLevel, DISPUTE_WINDOW, JOB_EVENTS_STREAM, MAX_FEE_BASIS_POINTS,
+    MAX_AGENT_JOB_HISTORY, MAX_DESCRIPTION_LENGTH, MAX_FAVORITES, MAX_NAME_LENGTH, MAX_PROPOSAL_LENGTH, MAX_TAGS_PER_JOB, RATING_HALF_LIFE,
 };
 use linera_sdk::{
-    linera_base_types::{AccountOwner, Amount},
+    linera_base_types::{Account, AccountOwner, Amount, StreamName, TimeDelta, Timestamp},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
@@ -38,9 +40,9 @@ impl linera_sdk::linera_base_types::WithContractAbi for JobMarketplaceContract {
 
 impl Contract for JobMarketplaceContract {
     type Message = Message;
-    type InstantiationArgument = ();
-    type Parameters = ();
-    type EventValue = ();
+    type InstantiationArgument = InstantiationArgument;
+    type Parameters = MarketplaceParameters;
+    type EventValue = JobEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
         let state = JobMarketplace::load(runtime.root_view_storage_context())
@@ -49,16 +51,27 @@ impl Contract for JobMarketplaceContract {
         JobMarketplaceContract { state, runtime }
     }
 
-    async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
+    async fn instantiate(&mut self, argument: Self::InstantiationArgument) {
+        let parameters = self.runtime.application_parameters();
+        assert!(
+            parameters.fee_basis_points <= MAX_FEE_BASIS_POINTS,
+            "Fee basis points must not exceed {MAX_FEE_BASIS_POINTS}"
+        );
+
         // Initialize all IDs starting at 1
         self.state.next_job_id_mut().set(1);
         self.state.next_rating_id_mut().set(1);
         self.state.next_dispute_id_mut().set(1);
         self.state.next_message_id_mut().set(1);
+
+        // Marketplace configuration
+        self.state.admin_mut().set(Some(argument.admin));
+        self.state.min_job_payment_mut().set(argument.min_job_payment);
+        self.state.rating_epoch_mut().set(self.runtime.system_time());
     }
 
     async fn execute_operation(&mut self, operation: Operation) -> Self::Response {
-        match operation {
+        let result = match operation {
             // ===== Job Operations =====
             Operation::PostJob {
                 title,
@@ -68,17 +81,41 @@ impl Contract for JobMarketplaceContract {
                 tags,
                 deadline,
                 milestones,
-            } => self.post_job(title, description, payment, category, tags, deadline, milestones).await,
+                auto_accept_at,
+            } => {
+                return self
+                    .post_job(title, description, payment, category, tags, deadline, milestones, auto_accept_at)
+                    .await
+                    .map(|job_id| OperationResponse::JobPosted { job_id });
+            }
             
+            Operation::EditJob {
+                job_id,
+                description,
+                payment,
+            } => self.edit_job(job_id, description, payment).await,
+
             Operation::CancelJob { job_id } => self.cancel_job(job_id).await,
 
+            Operation::ExpireJob { job_id } => self.expire_job(job_id).await,
+
+            Operation::AddFavorite { job_id } => self.add_favorite(job_id).await,
+
+            Operation::RemoveFavorite { job_id } => self.remove_favorite(job_id).await,
+
             // ===== Bidding Operations =====
             Operation::PlaceBid {
                 job_id,
                 amount,
                 proposal,
                 estimated_days,
-            } => self.place_bid(job_id, amount, proposal, estimated_days).await,
+                stake,
+            } => {
+                return self
+                    .place_bid(job_id, amount, proposal, estimated_days, stake)
+                    .await
+                    .map(|bid_id| OperationResponse::BidPlaced { bid_id });
+            }
             
             Operation::WithdrawBid { job_id } => self.withdraw_bid(job_id).await,
             
@@ -88,6 +125,14 @@ impl Contract for JobMarketplaceContract {
                 bid_amount,
             } => self.accept_bid(job_id, agent, bid_amount).await,
 
+            Operation::FinalizeAuction { job_id } => self.finalize_auction(job_id).await,
+
+            Operation::ReassignJob { job_id, new_agent } => self.reassign_job(job_id, new_agent).await,
+
+            Operation::ConfirmJob { job_id } => self.confirm_job(job_id).await,
+
+            Operation::ReopenJob { job_id } => self.reopen_job(job_id).await,
+
             // ===== Work Delivery Operations =====
             Operation::SubmitMilestone {
                 job_id,
@@ -99,6 +144,11 @@ impl Contract for JobMarketplaceContract {
                 job_id,
                 milestone_id,
             } => self.approve_milestone(job_id, milestone_id).await,
+
+            Operation::ReleaseMilestone {
+                job_id,
+                milestone_id,
+            } => self.release_milestone(job_id, milestone_id).await,
             
             Operation::RequestRevision {
                 job_id,
@@ -108,6 +158,12 @@ impl Contract for JobMarketplaceContract {
             
             Operation::CompleteJob { job_id } => self.complete_job(job_id).await,
 
+            Operation::ReleasePayment { job_id } => self.release_payment(job_id).await,
+
+            Operation::SettleJob { job_id } => self.settle_job(job_id).await,
+
+            Operation::AbandonJob { job_id } => self.abandon_job(job_id).await,
+
             // ===== Agent Operations =====
             Operation::RegisterAgent {
                 name,
@@ -135,6 +191,10 @@ impl Contract for JobMarketplaceContract {
                 .await
             }
             
+            Operation::SetAvailability { available } => self.set_availability(available).await,
+
+            Operation::DeregisterAgent => self.deregister_agent().await,
+
             Operation::RequestVerification { level, proof_data } => {
                 self.request_verification(level, proof_data).await
             }
@@ -146,6 +206,12 @@ impl Contract for JobMarketplaceContract {
                 review,
             } => self.rate_agent(job_id, rating, review).await,
 
+            Operation::RateClient {
+                job_id,
+                rating,
+                review,
+            } => self.rate_client(job_id, rating, review).await,
+
             // ===== Dispute Operations =====
             Operation::OpenDispute { job_id, reason } => self.open_dispute(job_id, reason).await,
             
@@ -161,6 +227,11 @@ impl Contract for JobMarketplaceContract {
                 notes,
             } => self.resolve_dispute(dispute_id, resolution, refund_percentage, notes).await,
 
+            // ===== Moderation Operations =====
+            Operation::AdminRemoveJob { job_id } => self.admin_remove_job(job_id).await,
+
+            Operation::BanAgent { agent } => self.ban_agent(agent).await,
+
             // ===== Messaging Operations =====
             Operation::SendMessage {
                 job_id,
@@ -171,14 +242,31 @@ impl Contract for JobMarketplaceContract {
             Operation::MarkMessagesRead { message_ids } => {
                 self.mark_messages_read(message_ids).await
             }
-        }
+        };
+
+        result.map(|()| OperationResponse::Ok)
     }
 
     async fn execute_message(&mut self, message: Message) {
         match message {
-            Message::JobPosted { job_id: _, title: _, payment: _, category: _ } => {
-                // Cross-chain job notification
-                // Would be handled by storing notification state if needed
+            Message::JobPosted { job_id, title, payment, category } => {
+                // Record the listing so the job board can aggregate jobs across chains
+                let origin_chain_id = self
+                    .runtime
+                    .message_origin_chain_id()
+                    .expect("Incoming message must have an origin chain");
+                let remote_job = RemoteJob {
+                    origin_chain_id,
+                    job_id,
+                    title,
+                    payment,
+                    category,
+                    received_at: self.runtime.system_time(),
+                };
+                self.state
+                    .remote_jobs_mut()
+                    .insert(&(origin_chain_id, job_id), remote_job)
+                    .expect("Failed to insert remote job");
             }
             Message::BidAccepted { job_id: _, agent: _, amount: _ } => {
                 // Bid accepted notification
@@ -213,285 +301,1286 @@ impl JobMarketplaceContract {
             .ok_or(JobMarketplaceError::NotAuthorized)
     }
 
-    // ==================== JOB OPERATIONS ====================
-
-    /// Post a new job with escrow
-    async fn post_job(
-        &mut self,
-        title: String,
-        description: String,
-        payment: Amount,
-        category: JobCategory,
-        tags: Vec<String>,
-        deadline: Option<u64>,
-        milestone_inputs: Vec<MilestoneInput>,
-    ) -> Result<(), JobMarketplaceError> {
+    /// Get the caller if they are the marketplace admin
+    fn get_admin(&mut self) -> Result<AccountOwner, JobMarketplaceError> {
         let caller = self.get_caller()?;
-
-        // Validate milestones total 100%
-        if !milestone_inputs.is_empty() {
-            let total: u8 = milestone_inputs.iter().map(|m| m.payment_percentage).sum();
-            if total != 100 {
-                return Err(JobMarketplaceError::InvalidMilestonePercentages);
-            }
+        if *self.state.admin().get() != Some(caller) {
+            return Err(JobMarketplaceError::NotAuthorized);
         }
+        Ok(caller)
+    }
 
-        // Get next job ID
-        let job_id = *self.state.next_job_id().get();
-        self.state.next_job_id_mut().set(job_id + 1);
-
-        // Convert milestones
-        let milestones: Vec<Milestone> = milestone_inputs
-            .into_iter()
-            .enumerate()
-            .map(|(i, m)| Milestone {
-                id: i as u64,
-                title: m.title,
-                description: m.description,
-                payment_percentage: m.payment_percentage,
-                status: MilestoneStatus::Pending,
-                due_date: m.due_days.map(|days| {
-                    let current = self.runtime.system_time();
-                    linera_sdk::linera_base_types::Timestamp::from(
-                        current.micros() + (days as u64 * 24 * 60 * 60 * 1_000_000)
-                    )
-                }),
-            })
-            .collect();
-
-        // Convert deadline
-        let deadline_ts = deadline.map(|ts| {
-            linera_sdk::linera_base_types::Timestamp::from(ts * 1_000_000)
-        });
-
-        // Create job
-        let job = Job {
-            id: job_id,
-            client: caller,
-            description,
-            payment,
-            status: JobStatus::Posted,
-            agent: None,
-            bids: vec![],
-            created_at: self.runtime.system_time(),
-            title: title.clone(),
-            category,
-            tags,
-            deadline: deadline_ts,
-            milestones,
-            accepted_bid_amount: None,
-            escrow_id: None,
-        };
+    /// Reject agents banned by the admin
+    async fn ensure_not_banned(&mut self, agent: AccountOwner) -> Result<(), JobMarketplaceError> {
+        if self.state.banned().contains_key(&agent).await.expect("Failed to get banned agents") {
+            return Err(JobMarketplaceError::AgentBanned);
+        }
+        Ok(())
+    }
 
-        // Store job
-        self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to insert job");
+    /// Convert a Unix timestamp in seconds, rejecting times too far out to represent
+    fn timestamp_from_secs(secs: u64) -> Result<Timestamp, JobMarketplaceError> {
+        secs.checked_mul(1_000_000)
+            .map(Timestamp::from)
+            .ok_or(JobMarketplaceError::InvalidDeadline)
+    }
 
+    /// Reject empty or over-long required text inputs
+    fn validate_text(text: &str, max_length: usize) -> Result<(), JobMarketplaceError> {
+        if text.trim().is_empty() {
+            return Err(JobMarketplaceError::InvalidInput);
+        }
+        if text.chars().count() > max_length {
+            return Err(JobMarketplaceError::InputTooLong);
+        }
         Ok(())
     }
 
-    /// Cancel a posted job
-    async fn cancel_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
-        let caller = self.get_caller()?;
+    /// Platform fee owed on a payout, rounded down so the split never exceeds the amount
+    fn platform_fee(&mut self, amount: Amount) -> Amount {
+        let basis_points = self
+            .runtime
+            .application_parameters()
+            .fee_basis_points
+            .min(MAX_FEE_BASIS_POINTS) as u128;
+        let max = MAX_FEE_BASIS_POINTS as u128;
+        let attos = amount.to_attos();
+        // Split the multiplication to avoid overflowing u128 on large amounts
+        Amount::from_attos(attos / max * basis_points + attos % max * basis_points / max)
+    }
 
-        let mut job = self.state
-            .jobs()
-            .get(&job_id)
-            .await
-            .expect("Failed to get job")
-            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+    /// Emit a job lifecycle event on the marketplace stream
+    fn emit_event(&mut self, event: JobEvent) {
+        self.runtime.emit(StreamName::from(JOB_EVENTS_STREAM), &event);
+    }
 
-        // Only client can cancel
-        if job.client != caller {
-            return Err(JobMarketplaceError::NotAuthorized);
+    /// Account owned by this application on the current chain, used to hold escrow
+    fn escrow_account(&mut self) -> Account {
+        Account {
+            chain_id: self.runtime.chain_id(),
+            owner: self.runtime.application_id().into(),
         }
+    }
 
-        // Can only cancel if Posted (no accepted bid)
-        if job.status != JobStatus::Posted {
-            return Err(JobMarketplaceError::InvalidStatus);
+    /// Claim funds from the owner's balance into the application's escrow for a job
+    async fn lock_escrow(
+        &mut self,
+        job_id: u64,
+        owner: AccountOwner,
+        amount: Amount,
+    ) -> Result<(), JobMarketplaceError> {
+        if self.runtime.owner_balance(owner) < amount {
+            return Err(JobMarketplaceError::InsufficientFunds);
         }
 
-        job.status = JobStatus::Cancelled;
+        let held = self.state
+            .escrow_balance()
+            .get(&job_id)
+            .await
+            .expect("Failed to get escrow balance")
+            .unwrap_or(Amount::ZERO);
+        let held = held.try_add(amount).map_err(|_| JobMarketplaceError::InvalidAmount)?;
+
+        let destination = self.escrow_account();
+        self.runtime.transfer(owner, destination, amount);
 
         self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to update job");
+            .escrow_balance_mut()
+            .insert(&job_id, held)
+            .expect("Failed to update escrow balance");
 
         Ok(())
     }
 
-    // ==================== BIDDING OPERATIONS ====================
+    /// Debit funds held in escrow for a job, returning an error if not enough is held
+    async fn debit_escrow(&mut self, job_id: u64, amount: Amount) -> Result<(), JobMarketplaceError> {
+        let held = self.state
+            .escrow_balance()
+            .get(&job_id)
+            .await
+            .expect("Failed to get escrow balance")
+            .unwrap_or(Amount::ZERO);
+        let remaining = held.try_sub(amount).map_err(|_| JobMarketplaceError::InsufficientFunds)?;
+
+        if remaining == Amount::ZERO {
+            self.state
+                .escrow_balance_mut()
+                .remove(&job_id)
+                .expect("Failed to update escrow balance");
+        } else {
+            self.state
+                .escrow_balance_mut()
+                .insert(&job_id, remaining)
+                .expect("Failed to update escrow balance");
+        }
 
-    /// Place a bid on a job with amount and proposal
-    async fn place_bid(
+        Ok(())
+    }
+
+    /// Send funds held in escrow for a job to the given owner
+    async fn transfer_from_escrow(
         &mut self,
         job_id: u64,
+        recipient: AccountOwner,
         amount: Amount,
-        proposal: String,
-        estimated_days: u32,
     ) -> Result<(), JobMarketplaceError> {
-        let caller = self.get_caller()?;
+        self.debit_escrow(job_id, amount).await?;
+        self.send_from_application(recipient, amount);
 
-        // Check if agent is registered
-        let agents_list = self.state.agents().indices().await.expect("Failed to get agents");
-        if !agents_list.contains(&caller) {
-            return Err(JobMarketplaceError::AgentNotRegistered);
-        }
+        Ok(())
+    }
 
-        // Get job
-        let mut job = self.state
-            .jobs()
-            .get(&job_id)
-            .await
-            .expect("Failed to get job")
-            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+    /// Send funds from the application's account to an owner on this chain
+    fn send_from_application(&mut self, recipient: AccountOwner, amount: Amount) {
+        let source = self.runtime.application_id().into();
+        let destination = Account {
+            chain_id: self.runtime.chain_id(),
+            owner: recipient,
+        };
+        self.runtime.transfer(source, destination, amount);
+    }
 
-        // Cannot bid on own job
-        if job.client == caller {
-            return Err(JobMarketplaceError::CannotBidOwnJob);
+    /// Pay an agent out of a job's escrow, sending the platform fee to the fee recipient
+    async fn pay_agent_from_escrow(
+        &mut self,
+        job_id: u64,
+        agent: AccountOwner,
+        amount: Amount,
+    ) -> Result<(), JobMarketplaceError> {
+        let fee = self.platform_fee(amount);
+        let agent_share = amount.try_sub(fee).map_err(|_| JobMarketplaceError::InvalidAmount)?;
+
+        // Debit the whole payout first, so a shortfall fails before anything is sent
+        self.debit_escrow(job_id, amount).await?;
+        if fee > Amount::ZERO {
+            let fee_recipient = self.runtime.application_parameters().fee_recipient;
+            self.send_from_application(fee_recipient, fee);
         }
-
-        // Check if job is in Posted status
-        if job.status != JobStatus::Posted {
-            return Err(JobMarketplaceError::InvalidStatus);
+        if agent_share > Amount::ZERO {
+            self.send_from_application(agent, agent_share);
         }
 
-        // Check deadline
-        if let Some(deadline) = job.deadline {
-            if self.runtime.system_time() > deadline {
-                return Err(JobMarketplaceError::DeadlinePassed);
+        self.emit_event(JobEvent::PaymentReleased {
+            job_id,
+            agent,
+            amount: agent_share,
+            fee,
+        });
+
+        Ok(())
+    }
+
+    /// Update the escrow record of a job once its funds have been settled
+    async fn settle_escrow_info(&mut self, job: &Job, status: EscrowStatus) {
+        if let Some(escrow_id) = job.escrow_id {
+            if let Some(mut escrow) = self.state.escrow().get(&escrow_id).await.expect("Failed to get escrow") {
+                escrow.status = status;
+                escrow.released_at = Some(self.runtime.system_time());
+                self.state.escrow_mut().insert(&escrow_id, escrow).expect("Failed to update escrow");
             }
         }
+    }
 
-        // Check if already bid
-        if job.bids.iter().any(|b| b.agent == caller) {
-            return Err(JobMarketplaceError::AlreadyBid);
+    /// Credit a completed job to the agent's profile and history
+    async fn record_job_completed(&mut self, agent_owner: AccountOwner, job_id: u64) {
+        if let Some(mut agent_profile) = self.state.agents().get(&agent_owner).await.expect("Failed to get agent") {
+            agent_profile.jobs_completed += 1;
+            // Update success rate
+            let total_jobs = agent_profile.jobs_completed;
+            agent_profile.success_rate = ((agent_profile.success_rate as u64 * (total_jobs - 1) + 100) / total_jobs) as u8;
+            self.state.agents_mut().insert(&agent_owner, agent_profile).expect("Failed to update agent");
         }
 
-        // Validate amount
-        if amount == Amount::ZERO {
-            return Err(JobMarketplaceError::InvalidAmount);
+        let mut history = self
+            .state
+            .agent_jobs()
+            .get(&agent_owner)
+            .await
+            .expect("Failed to get agent jobs")
+            .unwrap_or_default();
+        history.push(job_id);
+        if history.len() > MAX_AGENT_JOB_HISTORY {
+            history.remove(0);
         }
-
-        // Add bid
-        let bid = Bid {
-            agent: caller,
-            bid_id: job.bids.len() as u64,
-            timestamp: self.runtime.system_time(),
-            amount,
-            proposal,
-            estimated_days,
-        };
-        job.bids.push(bid);
-
-        // Update job
         self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to update job");
-
-        Ok(())
+            .agent_jobs_mut()
+            .insert(&agent_owner, history)
+            .expect("Failed to update agent jobs");
     }
 
-    /// Withdraw a bid
-    async fn withdraw_bid(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
-        let caller = self.get_caller()?;
+    /// Undo `record_job_completed` for a completed job the agent later lost a dispute over
+    async fn revoke_job_completed(&mut self, agent_owner: AccountOwner, job_id: u64) {
+        if let Some(mut agent_profile) = self.state.agents().get(&agent_owner).await.expect("Failed to get agent") {
+            let total_jobs = agent_profile.jobs_completed;
+            if total_jobs > 0 {
+                agent_profile.jobs_completed -= 1;
+                // Invert the running average, back to the initial rate with no jobs left
+                agent_profile.success_rate = if total_jobs == 1 {
+                    100
+                } else {
+                    ((agent_profile.success_rate as u64 * total_jobs).saturating_sub(100) / (total_jobs - 1)).min(100) as u8
+                };
+                self.state.agents_mut().insert(&agent_owner, agent_profile).expect("Failed to update agent");
+            }
+        }
 
-        let mut job = self.state
-            .jobs()
-            .get(&job_id)
+        let mut history = self
+            .state
+            .agent_jobs()
+            .get(&agent_owner)
             .await
-            .expect("Failed to get job")
-            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+            .expect("Failed to get agent jobs")
+            .unwrap_or_default();
+        history.retain(|id| *id != job_id);
+        self.state
+            .agent_jobs_mut()
+            .insert(&agent_owner, history)
+            .expect("Failed to update agent jobs");
+    }
 
-        // Check if job is still posted
-        if job.status != JobStatus::Posted {
-            return Err(JobMarketplaceError::InvalidStatus);
+    /// Count a newly assigned job against the agent's active jobs
+    async fn assign_active_job(&mut self, agent_owner: AccountOwner) {
+        if let Some(mut agent_profile) = self.state.agents().get(&agent_owner).await.expect("Failed to get agent") {
+            agent_profile.active_jobs += 1;
+            self.state.agents_mut().insert(&agent_owner, agent_profile).expect("Failed to update agent");
         }
+    }
 
-        // Find and remove bid
-        let original_len = job.bids.len();
-        job.bids.retain(|b| b.agent != caller);
+    /// Reject assigning another job to an agent already at the configured limit
+    async fn ensure_agent_capacity(&mut self, agent_owner: AccountOwner) -> Result<(), JobMarketplaceError> {
+        let Some(max) = self.runtime.application_parameters().max_active_jobs_per_agent else {
+            return Ok(());
+        };
+        let active_jobs = self.state
+            .agents()
+            .get(&agent_owner)
+            .await
+            .expect("Failed to get agent")
+            .map_or(0, |profile| profile.active_jobs);
+        if active_jobs >= max {
+            return Err(JobMarketplaceError::AgentAtCapacity);
+        }
+        Ok(())
+    }
 
-        if job.bids.len() == original_len {
-            return Err(JobMarketplaceError::BidNotFound);
+    /// Remove a finished or cancelled job from the agent's active jobs
+    async fn release_active_job(&mut self, agent_owner: AccountOwner) {
+        if let Some(mut agent_profile) = self.state.agents().get(&agent_owner).await.expect("Failed to get agent") {
+            agent_profile.active_jobs = agent_profile.active_jobs.saturating_sub(1);
+            self.state.agents_mut().insert(&agent_owner, agent_profile).expect("Failed to update agent");
         }
+    }
 
+    /// Number of unfinished jobs the client currently has
+    async fn client_open_jobs(&mut self, client: AccountOwner) -> u64 {
         self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to update job");
+            .open_jobs()
+            .get(&client)
+            .await
+            .expect("Failed to get open jobs")
+            .unwrap_or(0)
+    }
 
-        Ok(())
+    /// Count a newly posted job against the client's open jobs
+    async fn open_client_job(&mut self, client: AccountOwner) {
+        let open = self.client_open_jobs(client).await;
+        self.state
+            .open_jobs_mut()
+            .insert(&client, open + 1)
+            .expect("Failed to update open jobs");
     }
 
-    /// Accept a bid (locks payment in escrow)
-    async fn accept_bid(
+    /// Remove a finished, cancelled or expired job from the client's open jobs
+    async fn close_client_job(&mut self, client: AccountOwner) {
+        let open = self.client_open_jobs(client).await;
+        if open <= 1 {
+            self.state.open_jobs_mut().remove(&client).expect("Failed to update open jobs");
+        } else {
+            self.state
+                .open_jobs_mut()
+                .insert(&client, open - 1)
+                .expect("Failed to update open jobs");
+        }
+    }
+
+    /// Claim an agent's bid stake from their balance into the application
+    async fn lock_stake(
         &mut self,
         job_id: u64,
         agent: AccountOwner,
-        bid_amount: Amount,
+        amount: Amount,
     ) -> Result<(), JobMarketplaceError> {
-        let caller = self.get_caller()?;
+        if amount == Amount::ZERO {
+            return Ok(());
+        }
+        if self.runtime.owner_balance(agent) < amount {
+            return Err(JobMarketplaceError::InsufficientFunds);
+        }
 
-        let mut job = self.state
-            .jobs()
-            .get(&job_id)
-            .await
-            .expect("Failed to get job")
-            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+        let destination = self.escrow_account();
+        self.runtime.transfer(agent, destination, amount);
 
-        // Check if caller is the client
-        if job.client != caller {
-            return Err(JobMarketplaceError::NotAuthorized);
-        }
+        self.state
+            .bid_stakes_mut()
+            .insert(&(job_id, agent), amount)
+            .expect("Failed to update bid stake");
+
+        Ok(())
+    }
+
+    /// Send an agent's held stake for a job to the given owner (the agent, or the client when slashed)
+    async fn settle_stake(&mut self, job_id: u64, agent: AccountOwner, recipient: AccountOwner) {
+        let key = (job_id, agent);
+        let Some(stake) = self.state.bid_stakes().get(&key).await.expect("Failed to get bid stake") else {
+            return;
+        };
+        self.state.bid_stakes_mut().remove(&key).expect("Failed to update bid stake");
+        self.send_from_application(recipient, stake);
+    }
+
+    /// Return the stakes of every bid on a job, except the given agent's,
+    /// clearing the refunded bids' stake amounts
+    async fn refund_bid_stakes(&mut self, job: &mut Job, except: Option<AccountOwner>) {
+        for bid in &mut job.bids {
+            if Some(bid.agent) != except {
+                self.settle_stake(job.id, bid.agent, bid.agent).await;
+                bid.stake = Amount::ZERO;
+            }
+        }
+    }
+
+    /// Record an agent's bid on a job that is open for bidding
+    async fn add_open_bid(&mut self, agent: AccountOwner, job_id: u64) {
+        let mut job_ids = self
+            .state
+            .open_bids()
+            .get(&agent)
+            .await
+            .expect("Failed to get open bids")
+            .unwrap_or_default();
+        job_ids.push(job_id);
+        self.state
+            .open_bids_mut()
+            .insert(&agent, job_ids)
+            .expect("Failed to update open bids");
+    }
+
+    /// Forget an agent's bid on a job, once withdrawn or once the job stops taking bids
+    async fn remove_open_bid(&mut self, agent: AccountOwner, job_id: u64) {
+        let Some(mut job_ids) = self.state.open_bids().get(&agent).await.expect("Failed to get open bids") else {
+            return;
+        };
+        job_ids.retain(|id| *id != job_id);
+        if job_ids.is_empty() {
+            self.state.open_bids_mut().remove(&agent).expect("Failed to update open bids");
+        } else {
+            self.state
+                .open_bids_mut()
+                .insert(&agent, job_ids)
+                .expect("Failed to update open bids");
+        }
+    }
+
+    /// Drop a job from its bidders' open bids when it leaves the Posted status
+    async fn close_bidding(&mut self, job: &Job) {
+        for bid in &job.bids {
+            self.remove_open_bid(bid.agent, job.id).await;
+        }
+    }
+
+    /// Withdraw an agent's bids from every job still open for bidding, returning their stakes.
+    /// Only visits the jobs in the agent's open bids, not every job ever posted
+    async fn withdraw_open_bids(&mut self, agent: AccountOwner) {
+        let job_ids = self
+            .state
+            .open_bids()
+            .get(&agent)
+            .await
+            .expect("Failed to get open bids")
+            .unwrap_or_default();
+        self.state.open_bids_mut().remove(&agent).expect("Failed to update open bids");
+
+        for job_id in job_ids {
+            let Some(mut job) = self.state.jobs().get(&job_id).await.expect("Failed to get job") else {
+                continue;
+            };
+            if job.status != JobStatus::Posted || !job.bids.iter().any(|b| b.agent == agent) {
+                continue;
+            }
+
+            job.bids.retain(|b| b.agent != agent);
+            self.settle_stake(job_id, agent, agent).await;
+            let total_bids = self.state.total_bids().get().saturating_sub(1);
+            self.state.total_bids_mut().set(total_bids);
+
+            self.save_job(job);
+        }
+    }
+
+    /// Add `delta` jobs to the count for a status (negative to remove)
+    async fn adjust_status_count(&mut self, status: JobStatus, delta: i64) {
+        let count = self
+            .state
+            .status_counts()
+            .get(&status)
+            .await
+            .expect("Failed to get status count")
+            .unwrap_or(0);
+        let count = count.saturating_add_signed(delta);
+        self.state
+            .status_counts_mut()
+            .insert(&status, count)
+            .expect("Failed to update status count");
+    }
+
+    /// Store a changed job, stamping it as updated now
+    fn save_job(&mut self, mut job: Job) {
+        job.updated_at = self.runtime.system_time();
+        let job_id = job.id;
+        self.state.jobs_mut().insert(&job_id, job).expect("Failed to update job");
+    }
+
+    /// Move a job to a new status, keeping the status counts in sync
+    async fn set_job_status(&mut self, job: &mut Job, status: JobStatus) {
+        if job.status != status {
+            self.adjust_status_count(job.status, -1).await;
+            self.adjust_status_count(status, 1).await;
+            job.status = status;
+            self.emit_event(JobEvent::JobStatusChanged {
+                job_id: job.id,
+                status,
+            });
+        }
+    }
+
+    /// Add to the total payment volume, flagging it as overflowed instead of
+    /// blocking new jobs once it no longer fits in an Amount
+    fn add_payment_volume(&mut self, amount: Amount) {
+        match self.state.total_payment_volume().get().try_add(amount) {
+            Ok(volume) => self.state.total_payment_volume_mut().set(volume),
+            Err(_) => self.state.payment_volume_overflowed_mut().set(true),
+        }
+    }
+
+    /// Remove a lowered payment from the total payment volume
+    fn subtract_payment_volume(&mut self, amount: Amount) {
+        // An overflowed total is no longer exact, so it is left as is
+        if !*self.state.payment_volume_overflowed().get() {
+            let volume = self.state.total_payment_volume().get().saturating_sub(amount);
+            self.state.total_payment_volume_mut().set(volume);
+        }
+    }
+
+    /// Send whatever is still held in escrow for a job back to the given owner
+    async fn refund_remaining_escrow(
+        &mut self,
+        job_id: u64,
+        recipient: AccountOwner,
+    ) -> Result<(), JobMarketplaceError> {
+        let held = self.state
+            .escrow_balance()
+            .get(&job_id)
+            .await
+            .expect("Failed to get escrow balance")
+            .unwrap_or(Amount::ZERO);
+        if held > Amount::ZERO {
+            self.transfer_from_escrow(job_id, recipient, held).await?;
+        }
+
+        Ok(())
+    }
+
+    // ==================== JOB OPERATIONS ====================
+
+    /// Post a new job with escrow
+    #[allow(clippy::too_many_arguments)]
+    async fn post_job(
+        &mut self,
+        title: String,
+        description: String,
+        payment: Amount,
+        category: JobCategory,
+        tags: Vec<String>,
+        deadline: Option<u64>,
+        milestone_inputs: Vec<MilestoneInput>,
+        auto_accept_at: Option<u64>,
+    ) -> Result<u64, JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        Self::validate_text(&title, MAX_NAME_LENGTH)?;
+        Self::validate_text(&description, MAX_DESCRIPTION_LENGTH)?;
+
+        // Enforce the marketplace minimum payment
+        if payment < *self.state.min_job_payment().get() {
+            return Err(JobMarketplaceError::PaymentTooLow);
+        }
+
+        // Bound the number of tags to keep job state small
+        if tags.len() > MAX_TAGS_PER_JOB {
+            return Err(JobMarketplaceError::TooManyTags);
+        }
+
+        // Bound the number of unfinished jobs per client
+        if let Some(max) = self.runtime.application_parameters().max_open_jobs_per_client {
+            if self.client_open_jobs(caller).await >= max {
+                return Err(JobMarketplaceError::TooManyOpenJobs);
+            }
+        }
+
+        // Validate milestones are all nonzero and total exactly 100%
+        if !milestone_inputs.is_empty() {
+            let total = milestone_inputs.iter().try_fold(0u32, |total, m| {
+                if m.payment_percentage == 0 {
+                    return None;
+                }
+                total.checked_add(u32::from(m.payment_percentage))
+            });
+            if total != Some(100) {
+                return Err(JobMarketplaceError::InvalidMilestonePercentages);
+            }
+        }
+
+        // Convert milestones, deadline and auto-accept time before any funds move
+        let now = self.runtime.system_time();
+        let milestones = milestone_inputs
+            .into_iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let due_date = m
+                    .due_days
+                    .map(|days| {
+                        u64::from(days)
+                            .checked_mul(24 * 60 * 60 * 1_000_000)
+                            .and_then(|delay| now.micros().checked_add(delay))
+                            .map(Timestamp::from)
+                            .ok_or(JobMarketplaceError::InvalidDeadline)
+                    })
+                    .transpose()?;
+                Ok(Milestone {
+                    id: i as u64,
+                    title: m.title,
+                    description: m.description,
+                    payment_percentage: m.payment_percentage,
+                    status: MilestoneStatus::Pending,
+                    released: false,
+                    due_date,
+                })
+            })
+            .collect::<Result<Vec<_>, JobMarketplaceError>>()?;
+        let deadline_ts = deadline.map(Self::timestamp_from_secs).transpose()?;
+        let auto_accept_ts = auto_accept_at.map(Self::timestamp_from_secs).transpose()?;
+
+        // Get next job ID
+        let job_id = *self.state.next_job_id().get();
+
+        // Lock the payment in escrow before the job becomes visible
+        self.lock_escrow(job_id, caller, payment).await?;
+
+        self.state.next_job_id_mut().set(job_id + 1);
+
+        // Create job
+        let job = Job {
+            id: job_id,
+            client: caller,
+            description,
+            payment,
+            status: JobStatus::Posted,
+            agent: None,
+            bids: vec![],
+            created_at: self.runtime.system_time(),
+            title: title.clone(),
+            category,
+            tags,
+            deadline: deadline_ts,
+            auto_accept_at: auto_accept_ts,
+            milestones,
+            accepted_bid_amount: None,
+            escrow_id: None,
+            completed_at: None,
+            updated_at: self.runtime.system_time(),
+            accepted_at: None,
+            settled: false,
+        };
+
+        // Store job
+        self.state
+            .jobs_mut()
+            .insert(&job_id, job)
+            .expect("Failed to insert job");
+        self.open_client_job(caller).await;
+        self.adjust_status_count(JobStatus::Posted, 1).await;
+        self.add_payment_volume(payment);
+
+        // Announce the job to the job board chain
+        if let Some(job_board) = self.runtime.application_parameters().job_board_chain_id {
+            if job_board != self.runtime.chain_id() {
+                self.runtime.send_message(
+                    job_board,
+                    Message::JobPosted {
+                        job_id,
+                        title,
+                        payment,
+                        category,
+                    },
+                );
+            }
+        }
+
+        self.emit_event(JobEvent::JobPosted {
+            job_id,
+            client: caller,
+            payment,
+        });
+
+        Ok(job_id)
+    }
+
+    /// Edit a posted job, adjusting the escrow to a new payment
+    async fn edit_job(
+        &mut self,
+        job_id: u64,
+        description: Option<String>,
+        payment: Option<Amount>,
+    ) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Only client can edit
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Can only edit if Posted (no accepted bid)
+        if job.status != JobStatus::Posted {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        if let Some(ref description) = description {
+            Self::validate_text(description, MAX_DESCRIPTION_LENGTH)?;
+        }
+
+        if let Some(payment) = payment {
+            if payment < *self.state.min_job_payment().get() {
+                return Err(JobMarketplaceError::PaymentTooLow);
+            }
+            // Existing bids must remain payable from the escrow
+            if job.bids.iter().any(|b| b.amount > payment) {
+                return Err(JobMarketplaceError::InvalidAmount);
+            }
+
+            if payment > job.payment {
+                let increase = payment.saturating_sub(job.payment);
+                self.lock_escrow(job_id, caller, increase).await?;
+                self.add_payment_volume(increase);
+            } else if payment < job.payment {
+                let decrease = job.payment.saturating_sub(payment);
+                self.transfer_from_escrow(job_id, caller, decrease).await?;
+                self.subtract_payment_volume(decrease);
+            }
+            job.payment = payment;
+        }
+
+        if let Some(description) = description {
+            job.description = description;
+        }
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    /// Cancel a posted job
+    async fn cancel_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Only client can cancel
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Can only cancel if Posted (no accepted bid)
+        if job.status != JobStatus::Posted {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        // Return the locked payment to the client
+        self.refund_remaining_escrow(job_id, job.client).await?;
+        self.refund_bid_stakes(&mut job, None).await;
+        self.close_bidding(&job).await;
+        self.close_client_job(job.client).await;
+
+        self.set_job_status(&mut job, JobStatus::Cancelled).await;
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    /// Expire a posted job whose deadline has passed
+    async fn expire_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Only jobs still waiting for an accepted bid can expire
+        if job.status != JobStatus::Posted {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        // Jobs without a deadline never expire
+        let deadline = job.deadline.ok_or(JobMarketplaceError::DeadlineNotReached)?;
+        if self.runtime.system_time() <= deadline {
+            return Err(JobMarketplaceError::DeadlineNotReached);
+        }
+
+        // Return the locked payment to the client
+        self.refund_remaining_escrow(job_id, job.client).await?;
+        self.refund_bid_stakes(&mut job, None).await;
+        self.close_bidding(&job).await;
+        self.close_client_job(job.client).await;
+
+        self.set_job_status(&mut job, JobStatus::Expired).await;
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    /// Bookmark a job for the caller
+    async fn add_favorite(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        if !self.state.jobs().contains_key(&job_id).await.expect("Failed to get job") {
+            return Err(JobMarketplaceError::JobNotFound(job_id));
+        }
+
+        let mut favorites = self.state
+            .favorites()
+            .get(&caller)
+            .await
+            .expect("Failed to get favorites")
+            .unwrap_or_default();
+        if favorites.contains(&job_id) {
+            return Ok(());
+        }
+        if favorites.len() >= MAX_FAVORITES {
+            return Err(JobMarketplaceError::TooManyFavorites);
+        }
+        favorites.push(job_id);
+
+        self.state
+            .favorites_mut()
+            .insert(&caller, favorites)
+            .expect("Failed to update favorites");
+
+        Ok(())
+    }
+
+    /// Remove a job from the caller's bookmarks
+    async fn remove_favorite(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut favorites = self.state
+            .favorites()
+            .get(&caller)
+            .await
+            .expect("Failed to get favorites")
+            .unwrap_or_default();
+        favorites.retain(|id| *id != job_id);
+
+        if favorites.is_empty() {
+            self.state.favorites_mut().remove(&caller).expect("Failed to update favorites");
+        } else {
+            self.state
+                .favorites_mut()
+                .insert(&caller, favorites)
+                .expect("Failed to update favorites");
+        }
+
+        Ok(())
+    }
+
+    // ==================== BIDDING OPERATIONS ====================
+
+    /// Place a bid on a job with amount and proposal
+    async fn place_bid(
+        &mut self,
+        job_id: u64,
+        amount: Amount,
+        proposal: String,
+        estimated_days: u32,
+        stake: Amount,
+    ) -> Result<u64, JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        // Check if agent is registered and allowed to bid
+        let agents_list = self.state.agents().indices().await.expect("Failed to get agents");
+        if !agents_list.contains(&caller) {
+            return Err(JobMarketplaceError::AgentNotRegistered);
+        }
+        self.ensure_not_banned(caller).await?;
+
+        // Get job
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Cannot bid on own job
+        if job.client == caller {
+            return Err(JobMarketplaceError::CannotBidOwnJob);
+        }
 
         // Check if job is in Posted status
         if job.status != JobStatus::Posted {
             return Err(JobMarketplaceError::InvalidStatus);
         }
 
-        // Verify bid exists with the specified amount
-        let bid_exists = job.bids.iter().any(|b| b.agent == agent && b.amount == bid_amount);
-        if !bid_exists {
-            return Err(JobMarketplaceError::BidNotFound);
+        // Check deadline
+        if let Some(deadline) = job.deadline {
+            if self.runtime.system_time() > deadline {
+                return Err(JobMarketplaceError::DeadlinePassed);
+            }
+        }
+
+        // Bidding closes once the job can be auto-accepted
+        if let Some(auto_accept_at) = job.auto_accept_at {
+            if self.runtime.system_time() >= auto_accept_at {
+                return Err(JobMarketplaceError::DeadlinePassed);
+            }
+        }
+
+        // Check if already bid
+        if job.bids.iter().any(|b| b.agent == caller) {
+            return Err(JobMarketplaceError::AlreadyBid);
+        }
+
+        // Bound the proposal to keep job state small
+        if proposal.chars().count() > MAX_PROPOSAL_LENGTH {
+            return Err(JobMarketplaceError::ProposalTooLong);
+        }
+
+        // Validate amount (bids cannot exceed the escrowed payment)
+        if amount == Amount::ZERO || amount > job.payment {
+            return Err(JobMarketplaceError::InvalidAmount);
+        }
+
+        // Hold the agent's stake until the bid is resolved
+        if stake < self.runtime.application_parameters().min_bid_stake {
+            return Err(JobMarketplaceError::StakeTooLow);
+        }
+        self.lock_stake(job_id, caller, stake).await?;
+
+        // Add bid; IDs stay unique even after earlier bids are withdrawn
+        let bid_id = job.bids.iter().map(|b| b.bid_id + 1).max().unwrap_or(0);
+        let bid = Bid {
+            agent: caller,
+            bid_id,
+            timestamp: self.runtime.system_time(),
+            amount,
+            proposal,
+            estimated_days,
+            stake,
+        };
+        job.bids.push(bid);
+        let total_bids = *self.state.total_bids().get() + 1;
+        self.state.total_bids_mut().set(total_bids);
+        self.add_open_bid(caller, job_id).await;
+
+        // Update job
+        self.save_job(job);
+
+        self.emit_event(JobEvent::BidPlaced {
+            job_id,
+            agent: caller,
+            amount,
+        });
+
+        Ok(bid_id)
+    }
+
+    /// Withdraw a bid
+    async fn withdraw_bid(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Check if job is still posted
+        if job.status != JobStatus::Posted {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        // Only agents who bid on the job can withdraw
+        if !job.bids.iter().any(|b| b.agent == caller) {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Remove the caller's bid and return their stake
+        job.bids.retain(|b| b.agent != caller);
+        self.settle_stake(job_id, caller, caller).await;
+        self.remove_open_bid(caller, job_id).await;
+        let total_bids = self.state.total_bids().get().saturating_sub(1);
+        self.state.total_bids_mut().set(total_bids);
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    /// Accept a bid (locks payment in escrow)
+    async fn accept_bid(
+        &mut self,
+        job_id: u64,
+        agent: AccountOwner,
+        bid_amount: Amount,
+    ) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Check if caller is the client
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Check if job is in Posted status
+        if job.status != JobStatus::Posted {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        // Verify bid exists with the specified amount
+        let bid_exists = job.bids.iter().any(|b| b.agent == agent && b.amount == bid_amount);
+        if !bid_exists {
+            return Err(JobMarketplaceError::BidNotFound);
+        }
+
+        self.award_job(job, agent, bid_amount).await
+    }
+
+    /// Accept the lowest bid on a job whose auto-accept time has passed
+    async fn finalize_auction(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        if job.status != JobStatus::Posted {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        let auto_accept_at = job.auto_accept_at.ok_or(JobMarketplaceError::InvalidStatus)?;
+        if self.runtime.system_time() < auto_accept_at {
+            return Err(JobMarketplaceError::DeadlineNotReached);
+        }
+
+        // Lowest amount wins; ties go to the earliest bid. Without bids the job stays posted.
+        let (agent, bid_amount) = job
+            .bids
+            .iter()
+            .min_by_key(|b| (b.amount, b.timestamp))
+            .map(|b| (b.agent, b.amount))
+            .ok_or(JobMarketplaceError::BidNotFound)?;
+
+        self.award_job(job, agent, bid_amount).await
+    }
+
+    /// Assign a posted job to the winning bid, refunding the surplus and rejected stakes
+    async fn award_job(
+        &mut self,
+        mut job: Job,
+        agent: AccountOwner,
+        bid_amount: Amount,
+    ) -> Result<(), JobMarketplaceError> {
+        let job_id = job.id;
+        let client = job.client;
+
+        // The winner must still be a registered agent in good standing
+        if !self.state.agents().contains_key(&agent).await.expect("Failed to get agent") {
+            return Err(JobMarketplaceError::AgentNotRegistered);
+        }
+        self.ensure_not_banned(agent).await?;
+        self.ensure_agent_capacity(agent).await?;
+
+        // Refund the difference between the posted payment and the accepted bid
+        let surplus = job
+            .payment
+            .try_sub(bid_amount)
+            .map_err(|_| JobMarketplaceError::InvalidAmount)?;
+        if surplus > Amount::ZERO {
+            self.transfer_from_escrow(job_id, client, surplus).await?;
+        }
+
+        // Return the stakes of the rejected bids
+        self.refund_bid_stakes(&mut job, Some(agent)).await;
+        self.close_bidding(&job).await;
+
+        // Create escrow
+        let escrow_id = job_id; // Use job_id as escrow_id for simplicity
+        let escrow = EscrowInfo {
+            job_id,
+            client,
+            agent: Some(agent),
+            amount: bid_amount,
+            status: EscrowStatus::Locked,
+            locked_at: self.runtime.system_time(),
+            released_at: None,
+        };
+
+        self.state
+            .escrow_mut()
+            .insert(&escrow_id, escrow)
+            .expect("Failed to create escrow");
+
+        // Update job; work starts once the agent confirms
+        self.set_job_status(&mut job, JobStatus::AwaitingConfirmation).await;
+        job.agent = Some(agent);
+        job.accepted_bid_amount = Some(bid_amount);
+        job.escrow_id = Some(escrow_id);
+        job.accepted_at = Some(self.runtime.system_time());
+
+        self.save_job(job);
+
+        self.assign_active_job(agent).await;
+
+        self.emit_event(JobEvent::BidAccepted {
+            job_id,
+            agent,
+            amount: bid_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Move an in-progress job from an unresponsive agent to another bidder
+    async fn reassign_job(
+        &mut self,
+        job_id: u64,
+        new_agent: AccountOwner,
+    ) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Check if caller is the client
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Unconfirmed jobs can move once the confirmation window lapses, jobs in progress
+        // after the reassignment period. Jobs with paid milestones have settled part of
+        // the work and stay put
+        let params = self.runtime.application_parameters();
+        let wait_secs = match job.status {
+            JobStatus::AwaitingConfirmation => params.confirm_within_secs,
+            JobStatus::InProgress if !job.milestones.iter().any(|m| m.released) => params.reassign_after_secs,
+            _ => return Err(JobMarketplaceError::InvalidStatus),
+        };
+
+        let old_agent = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
+        let accepted_at = job.accepted_at.ok_or(JobMarketplaceError::InvalidStatus)?;
+        if self.runtime.system_time() <= accepted_at.saturating_add(TimeDelta::from_secs(wait_secs)) {
+            return Err(JobMarketplaceError::ReassignTooEarly);
         }
 
-        // Create escrow
-        let escrow_id = job_id; // Use job_id as escrow_id for simplicity
-        let escrow = EscrowInfo {
+        // The new agent must be another bidder in good standing
+        let bid_amount = job
+            .bids
+            .iter()
+            .find(|b| b.agent == new_agent && b.agent != old_agent)
+            .map(|b| b.amount)
+            .ok_or(JobMarketplaceError::BidNotFound)?;
+        self.ensure_not_banned(new_agent).await?;
+        self.ensure_agent_capacity(new_agent).await?;
+
+        // Top up or refund the escrow to match the new agent's bid
+        let held = self.state
+            .escrow_balance()
+            .get(&job_id)
+            .await
+            .expect("Failed to get escrow balance")
+            .unwrap_or(Amount::ZERO);
+        if bid_amount > held {
+            self.lock_escrow(job_id, caller, bid_amount.saturating_sub(held)).await?;
+        } else if bid_amount < held {
+            self.transfer_from_escrow(job_id, caller, held.saturating_sub(bid_amount)).await?;
+        }
+
+        // The previous agent forfeits their stake to the client
+        self.settle_stake(job_id, old_agent, caller).await;
+        self.release_active_job(old_agent).await;
+
+        if let Some(escrow_id) = job.escrow_id {
+            if let Some(mut escrow) = self.state.escrow().get(&escrow_id).await.expect("Failed to get escrow") {
+                escrow.agent = Some(new_agent);
+                escrow.amount = bid_amount;
+                self.state.escrow_mut().insert(&escrow_id, escrow).expect("Failed to update escrow");
+            }
+        }
+
+        // The new agent has to confirm before taking over
+        self.set_job_status(&mut job, JobStatus::AwaitingConfirmation).await;
+        job.agent = Some(new_agent);
+        job.accepted_bid_amount = Some(bid_amount);
+        job.accepted_at = Some(self.runtime.system_time());
+
+        self.save_job(job);
+
+        self.assign_active_job(new_agent).await;
+
+        self.emit_event(JobEvent::BidAccepted {
             job_id,
-            client: caller,
-            agent: Some(agent),
+            agent: new_agent,
             amount: bid_amount,
-            status: EscrowStatus::Locked,
-            locked_at: self.runtime.system_time(),
-            released_at: None,
-        };
+        });
 
-        self.state
-            .escrow_mut()
-            .insert(&escrow_id, escrow)
-            .expect("Failed to create escrow");
+        Ok(())
+    }
 
-        // Update job
-        job.status = JobStatus::InProgress;
-        job.agent = Some(agent);
-        job.accepted_bid_amount = Some(bid_amount);
-        job.escrow_id = Some(escrow_id);
+    /// Confirm an accepted bid, moving the job into progress
+    async fn confirm_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
 
-        // Set milestones to InProgress if first milestone
-        if !job.milestones.is_empty() {
-            job.milestones[0].status = MilestoneStatus::InProgress;
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Only the accepted agent can confirm
+        if job.agent != Some(caller) {
+            return Err(JobMarketplaceError::NotAuthorized);
         }
 
-        self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to update job");
+        if job.status != JobStatus::AwaitingConfirmation {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        // Agents taking over after a reassignment or reopening had their stake
+        // returned when another bid won, so they lock the minimum stake again
+        if self.state.bid_stakes().get(&(job_id, caller)).await.expect("Failed to get bid stake").is_none() {
+            let stake = self.runtime.application_parameters().min_bid_stake;
+            self.lock_stake(job_id, caller, stake).await?;
+            if let Some(bid) = job.bids.iter_mut().find(|b| b.agent == caller) {
+                bid.stake = stake;
+            }
+        }
+
+        // The reassignment period runs from confirmation
+        self.set_job_status(&mut job, JobStatus::InProgress).await;
+        job.accepted_at = Some(self.runtime.system_time());
+
+        // Start the first milestone unless work on it already began
+        if let Some(first) = job.milestones.first_mut() {
+            if first.status == MilestoneStatus::Pending {
+                first.status = MilestoneStatus::InProgress;
+            }
+        }
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    /// Return a job to bidding when the accepted agent did not confirm in time
+    async fn reopen_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Check if caller is the client
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        if job.status != JobStatus::AwaitingConfirmation {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        let agent_owner = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
+        let accepted_at = job.accepted_at.ok_or(JobMarketplaceError::InvalidStatus)?;
+        let confirm_within = self.runtime.application_parameters().confirm_within_secs;
+        if self.runtime.system_time() <= accepted_at.saturating_add(TimeDelta::from_secs(confirm_within)) {
+            return Err(JobMarketplaceError::ConfirmationPending);
+        }
+
+        // Lock the full posted payment again, since the surplus was refunded on acceptance
+        let held = self.state
+            .escrow_balance()
+            .get(&job_id)
+            .await
+            .expect("Failed to get escrow balance")
+            .unwrap_or(Amount::ZERO);
+        if job.payment > held {
+            self.lock_escrow(job_id, caller, job.payment.saturating_sub(held)).await?;
+        }
+
+        // The agent forfeits their stake to the client, and their bid
+        self.settle_stake(job_id, agent_owner, caller).await;
+        self.release_active_job(agent_owner).await;
+        let bid_count = job.bids.len();
+        job.bids.retain(|b| b.agent != agent_owner);
+        let total_bids = self
+            .state
+            .total_bids()
+            .get()
+            .saturating_sub((bid_count - job.bids.len()) as u64);
+        self.state.total_bids_mut().set(total_bids);
+        // The remaining bids are open again
+        for bid in &job.bids {
+            self.add_open_bid(bid.agent, job_id).await;
+        }
+
+        if let Some(escrow_id) = job.escrow_id.take() {
+            self.state.escrow_mut().remove(&escrow_id).expect("Failed to remove escrow");
+        }
+
+        self.set_job_status(&mut job, JobStatus::Posted).await;
+        job.agent = None;
+        job.accepted_bid_amount = None;
+        job.accepted_at = None;
+
+        self.save_job(job);
 
         Ok(())
     }
@@ -538,12 +1627,9 @@ impl JobMarketplaceContract {
         milestone.status = MilestoneStatus::Submitted;
 
         // Update job status to pending approval
-        job.status = JobStatus::PendingApproval;
+        self.set_job_status(&mut job, JobStatus::PendingApproval).await;
 
-        self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to update job");
+        self.save_job(job);
 
         Ok(())
     }
@@ -556,59 +1642,245 @@ impl JobMarketplaceContract {
     ) -> Result<(), JobMarketplaceError> {
         let caller = self.get_caller()?;
 
-        let mut job = self.state
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Check if caller is the client
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Find and update milestone
+        let milestone = job
+            .milestones
+            .iter_mut()
+            .find(|m| m.id == milestone_id)
+            .ok_or(JobMarketplaceError::MilestoneNotFound)?;
+
+        if milestone.status != MilestoneStatus::Submitted {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        milestone.status = MilestoneStatus::Approved;
+
+        // Find next pending milestone and set to in progress
+        if let Some(next) = job.milestones.iter_mut().find(|m| m.status == MilestoneStatus::Pending) {
+            next.status = MilestoneStatus::InProgress;
+        }
+        self.set_job_status(&mut job, JobStatus::InProgress).await;
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    /// Pay out an approved milestone, completing the job once all are released
+    async fn release_milestone(
+        &mut self,
+        job_id: u64,
+        milestone_id: u64,
+    ) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Check if caller is the client
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        if job.status != JobStatus::InProgress && job.status != JobStatus::PendingApproval {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        let agent_owner = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
+        let job_amount = job.accepted_bid_amount.unwrap_or(job.payment);
+
+        let milestone = job
+            .milestones
+            .iter_mut()
+            .find(|m| m.id == milestone_id)
+            .ok_or(JobMarketplaceError::MilestoneNotFound)?;
+
+        if milestone.status != MilestoneStatus::Approved || milestone.released {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+        milestone.released = true;
+        let percentage = milestone.payment_percentage as u128;
+
+        let held = self.state
+            .escrow_balance()
+            .get(&job_id)
+            .await
+            .expect("Failed to get escrow balance")
+            .unwrap_or(Amount::ZERO);
+        let all_released = job.milestones.iter().all(|m| m.released);
+
+        // The last release pays out everything left, so rounding never strands funds
+        let amount = if all_released {
+            held
+        } else {
+            let attos = job_amount.to_attos();
+            Amount::from_attos(attos / 100 * percentage + attos % 100 * percentage / 100).min(held)
+        };
+        if amount > Amount::ZERO {
+            self.pay_agent_from_escrow(job_id, agent_owner, amount).await?;
+        }
+
+        if all_released {
+            self.set_job_status(&mut job, JobStatus::Completed).await;
+            job.completed_at = Some(self.runtime.system_time());
+            self.settle_escrow_info(&job, EscrowStatus::Released).await;
+            self.close_client_job(job.client).await;
+            self.record_job_completed(agent_owner, job_id).await;
+            self.release_active_job(agent_owner).await;
+            self.settle_stake(job_id, agent_owner, agent_owner).await;
+        }
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    /// Request revision for a milestone
+    async fn request_revision(
+        &mut self,
+        job_id: u64,
+        milestone_id: u64,
+        _feedback: String,
+    ) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Check if caller is the client
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Find and update milestone
+        let milestone = job
+            .milestones
+            .iter_mut()
+            .find(|m| m.id == milestone_id)
+            .ok_or(JobMarketplaceError::MilestoneNotFound)?;
+
+        if milestone.status != MilestoneStatus::Submitted {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        milestone.status = MilestoneStatus::Rejected;
+        self.set_job_status(&mut job, JobStatus::InProgress).await;
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    /// Complete entire job (payment is held until the dispute window closes)
+    async fn complete_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Client approves completion
+        if job.client != caller {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Check status
+        if job.status != JobStatus::InProgress && job.status != JobStatus::PendingApproval {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        let agent_owner = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
+        let amount = job.accepted_bid_amount.unwrap_or(job.payment);
+
+        // Update job
+        self.set_job_status(&mut job, JobStatus::Completed).await;
+        job.completed_at = Some(self.runtime.system_time());
+
+        // Mark all milestones as approved
+        for milestone in &mut job.milestones {
+            milestone.status = MilestoneStatus::Approved;
+        }
+
+        self.save_job(job);
+
+        // Update client and agent stats
+        self.close_client_job(caller).await;
+        self.record_job_completed(agent_owner, job_id).await;
+        self.release_active_job(agent_owner).await;
+
+        self.emit_event(JobEvent::JobCompleted {
+            job_id,
+            agent: agent_owner,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Release a completed job's escrow to the agent once the dispute window has closed
+    async fn release_payment(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        let job = self.state
             .jobs()
             .get(&job_id)
             .await
             .expect("Failed to get job")
             .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
 
-        // Check if caller is the client
-        if job.client != caller {
-            return Err(JobMarketplaceError::NotAuthorized);
-        }
-
-        // Find and update milestone
-        let milestone = job
-            .milestones
-            .iter_mut()
-            .find(|m| m.id == milestone_id)
-            .ok_or(JobMarketplaceError::MilestoneNotFound)?;
-
-        if milestone.status != MilestoneStatus::Submitted {
+        if job.status != JobStatus::Completed {
             return Err(JobMarketplaceError::InvalidStatus);
         }
 
-        milestone.status = MilestoneStatus::Approved;
+        let completed_at = job.completed_at.ok_or(JobMarketplaceError::InvalidStatus)?;
+        if self.runtime.system_time() <= completed_at.saturating_add(DISPUTE_WINDOW) {
+            return Err(JobMarketplaceError::DisputeWindowOpen);
+        }
 
-        // Check if all milestones are approved
-        let all_approved = job.milestones.iter().all(|m| m.status == MilestoneStatus::Approved);
+        let agent_owner = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
 
-        if all_approved {
-            job.status = JobStatus::Completed;
-        } else {
-            // Find next pending milestone and set to in progress
-            if let Some(next) = job.milestones.iter_mut().find(|m| m.status == MilestoneStatus::Pending) {
-                next.status = MilestoneStatus::InProgress;
-            }
-            job.status = JobStatus::InProgress;
+        // Pay out everything still held for the job (the accepted amount)
+        let held = self.state
+            .escrow_balance()
+            .get(&job_id)
+            .await
+            .expect("Failed to get escrow balance")
+            .unwrap_or(Amount::ZERO);
+        if held == Amount::ZERO {
+            return Err(JobMarketplaceError::EscrowNotFound);
         }
+        self.pay_agent_from_escrow(job_id, agent_owner, held).await?;
 
-        self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to update job");
+        self.settle_escrow_info(&job, EscrowStatus::Released).await;
+        // The stake was held through the dispute window along with the payment
+        self.settle_stake(job_id, agent_owner, agent_owner).await;
 
         Ok(())
     }
 
-    /// Request revision for a milestone
-    async fn request_revision(
-        &mut self,
-        job_id: u64,
-        milestone_id: u64,
-        _feedback: String,
-    ) -> Result<(), JobMarketplaceError> {
+    /// Close out a completed job, leaving nothing held in escrow for it
+    async fn settle_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
         let caller = self.get_caller()?;
 
         let mut job = self.state
@@ -618,35 +1890,58 @@ impl JobMarketplaceContract {
             .expect("Failed to get job")
             .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
 
-        // Check if caller is the client
+        // Only the client can settle their job
         if job.client != caller {
             return Err(JobMarketplaceError::NotAuthorized);
         }
 
-        // Find and update milestone
-        let milestone = job
-            .milestones
-            .iter_mut()
-            .find(|m| m.id == milestone_id)
-            .ok_or(JobMarketplaceError::MilestoneNotFound)?;
+        if job.status != JobStatus::Completed || job.settled {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
 
-        if milestone.status != MilestoneStatus::Submitted {
+        let completed_at = job.completed_at.ok_or(JobMarketplaceError::InvalidStatus)?;
+        if self.runtime.system_time() <= completed_at.saturating_add(DISPUTE_WINDOW) {
             return Err(JobMarketplaceError::InvalidStatus);
         }
 
-        milestone.status = MilestoneStatus::Rejected;
-        job.status = JobStatus::InProgress;
+        // Payment the agent is still owed goes out first
+        let escrow_locked = match job.escrow_id {
+            Some(escrow_id) => self.state
+                .escrow()
+                .get(&escrow_id)
+                .await
+                .expect("Failed to get escrow")
+                .is_some_and(|escrow| escrow.status == EscrowStatus::Locked),
+            None => false,
+        };
+        if escrow_locked {
+            let agent_owner = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
+            let held = self.state
+                .escrow_balance()
+                .get(&job_id)
+                .await
+                .expect("Failed to get escrow balance")
+                .unwrap_or(Amount::ZERO);
+            if held > Amount::ZERO {
+                self.pay_agent_from_escrow(job_id, agent_owner, held).await?;
+            }
+            self.settle_escrow_info(&job, EscrowStatus::Released).await;
+        }
+        if let Some(agent_owner) = job.agent {
+            self.settle_stake(job_id, agent_owner, agent_owner).await;
+        }
 
-        self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to update job");
+        // Anything left over belongs to the client
+        self.refund_remaining_escrow(job_id, caller).await?;
+
+        job.settled = true;
+        self.save_job(job);
 
         Ok(())
     }
 
-    /// Complete entire job (releases remaining payment)
-    async fn complete_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+    /// Cancel an in-progress job the agent failed to deliver by its deadline
+    async fn abandon_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
         let caller = self.get_caller()?;
 
         let mut job = self.state
@@ -656,48 +1951,33 @@ impl JobMarketplaceContract {
             .expect("Failed to get job")
             .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
 
-        // Client approves completion
+        // Only the client can abandon their job
         if job.client != caller {
             return Err(JobMarketplaceError::NotAuthorized);
         }
 
-        // Check status
-        if job.status != JobStatus::InProgress && job.status != JobStatus::PendingApproval {
+        if job.status != JobStatus::InProgress {
             return Err(JobMarketplaceError::InvalidStatus);
         }
 
-        // Update job
-        job.status = JobStatus::Completed;
-
-        // Mark all milestones as approved
-        for milestone in &mut job.milestones {
-            milestone.status = MilestoneStatus::Approved;
+        // Jobs without a deadline cannot be abandoned
+        let deadline = job.deadline.ok_or(JobMarketplaceError::DeadlineNotReached)?;
+        if self.runtime.system_time() <= deadline {
+            return Err(JobMarketplaceError::DeadlineNotReached);
         }
 
-        self.state
-            .jobs_mut()
-            .insert(&job_id, job.clone())
-            .expect("Failed to update job");
+        let agent_owner = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
 
-        // Release escrow
-        if let Some(escrow_id) = job.escrow_id {
-            if let Some(mut escrow) = self.state.escrow().get(&escrow_id).await.expect("Failed to get escrow") {
-                escrow.status = EscrowStatus::Released;
-                escrow.released_at = Some(self.runtime.system_time());
-                self.state.escrow_mut().insert(&escrow_id, escrow).expect("Failed to update escrow");
-            }
-        }
+        // Refund the client and slash the agent's stake to them
+        self.refund_remaining_escrow(job_id, caller).await?;
+        self.settle_stake(job_id, agent_owner, caller).await;
 
-        // Update agent stats
-        if let Some(agent_owner) = job.agent {
-            if let Some(mut agent_profile) = self.state.agents().get(&agent_owner).await.expect("Failed to get agent") {
-                agent_profile.jobs_completed += 1;
-                // Update success rate
-                let total_jobs = agent_profile.jobs_completed;
-                agent_profile.success_rate = ((agent_profile.success_rate as u64 * (total_jobs - 1) + 100) / total_jobs) as u8;
-                self.state.agents_mut().insert(&agent_owner, agent_profile).expect("Failed to update agent");
-            }
-        }
+        self.set_job_status(&mut job, JobStatus::Cancelled).await;
+        self.settle_escrow_info(&job, EscrowStatus::Refunded).await;
+        self.close_client_job(caller).await;
+        self.release_active_job(agent_owner).await;
+
+        self.save_job(job);
 
         Ok(())
     }
@@ -713,6 +1993,10 @@ impl JobMarketplaceContract {
         hourly_rate: Option<Amount>,
     ) -> Result<(), JobMarketplaceError> {
         let caller = self.get_caller()?;
+        self.ensure_not_banned(caller).await?;
+
+        Self::validate_text(&name, MAX_NAME_LENGTH)?;
+        Self::validate_text(&service_description, MAX_DESCRIPTION_LENGTH)?;
 
         // Check if agent already registered
         let agents_list = self.state.agents().indices().await.expect("Failed to get agents");
@@ -727,6 +2011,10 @@ impl JobMarketplaceContract {
             jobs_completed: 0,
             total_rating_points: 0,
             total_ratings: 0,
+            average_rating: 0.0,
+            weighted_rating_points: 0.0,
+            weighted_rating_total: 0.0,
+            weighted_score: 0.0,
             registered_at: self.runtime.system_time(),
             verification_level: VerificationLevel::Unverified,
             skills,
@@ -735,6 +2023,9 @@ impl JobMarketplaceContract {
             availability: true,
             response_time_hours: 24,
             success_rate: 100,
+            active_jobs: 0,
+            disputes_involved: 0,
+            disputes_lost: 0,
         };
 
         self.state
@@ -764,6 +2055,13 @@ impl JobMarketplaceContract {
             .expect("Failed to get agent")
             .ok_or(JobMarketplaceError::AgentNotRegistered)?;
 
+        if let Some(ref n) = name {
+            Self::validate_text(n, MAX_NAME_LENGTH)?;
+        }
+        if let Some(ref desc) = service_description {
+            Self::validate_text(desc, MAX_DESCRIPTION_LENGTH)?;
+        }
+
         if let Some(n) = name {
             profile.name = n;
         }
@@ -791,6 +2089,60 @@ impl JobMarketplaceContract {
         Ok(())
     }
 
+    /// Toggle whether the agent is taking on new work
+    async fn set_availability(&mut self, available: bool) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let mut profile = self.state
+            .agents()
+            .get(&caller)
+            .await
+            .expect("Failed to get agent")
+            .ok_or(JobMarketplaceError::AgentNotRegistered)?;
+
+        profile.availability = available;
+
+        self.state
+            .agents_mut()
+            .insert(&caller, profile)
+            .expect("Failed to update agent");
+
+        Ok(())
+    }
+
+    /// Leave the marketplace
+    async fn deregister_agent(&mut self) -> Result<(), JobMarketplaceError> {
+        let caller = self.get_caller()?;
+
+        let profile = self.state
+            .agents()
+            .get(&caller)
+            .await
+            .expect("Failed to get agent")
+            .ok_or(JobMarketplaceError::AgentNotRegistered)?;
+
+        // Agents cannot vanish in the middle of a job
+        if profile.active_jobs > 0 {
+            return Err(JobMarketplaceError::HasActiveJobs);
+        }
+
+        self.state
+            .agents_mut()
+            .remove(&caller)
+            .expect("Failed to deregister agent");
+
+        // A re-registered agent starts over, so the history indexes go with the profile.
+        // The ratings themselves stay, as clients' records of past jobs
+        self.state.agent_jobs_mut().remove(&caller).expect("Failed to update agent jobs");
+        self.state.agent_ratings_mut().remove(&caller).expect("Failed to update agent ratings");
+
+        // Any agent can deregister, so this must not scan every job: it only
+        // visits the jobs in the agent's open bids
+        self.withdraw_open_bids(caller).await;
+
+        Ok(())
+    }
+
     /// Request verification upgrade
     async fn request_verification(
         &mut self,
@@ -828,7 +2180,7 @@ impl JobMarketplaceContract {
         review: String,
     ) -> Result<(), JobMarketplaceError> {
         // Validate rating is 1-5
-        if rating < 1 || rating > 5 {
+        if !(1..=5).contains(&rating) {
             return Err(JobMarketplaceError::InvalidRating);
         }
 
@@ -842,8 +2194,8 @@ impl JobMarketplaceContract {
             .expect("Failed to get job")
             .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
 
-        // Check if job is completed
-        if job.status != JobStatus::Completed {
+        // Check if job is completed and still open for ratings
+        if job.status != JobStatus::Completed || job.settled {
             return Err(JobMarketplaceError::InvalidStatus);
         }
 
@@ -855,12 +2207,23 @@ impl JobMarketplaceContract {
         // Get the agent
         let agent_owner = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
 
+        // Each job can only be rated once
+        if self.state
+            .rated_jobs()
+            .contains_key(&job_id)
+            .await
+            .expect("Failed to check rated jobs")
+        {
+            return Err(JobMarketplaceError::AlreadyRated);
+        }
+
         // Create rating
         let rating_id = *self.state.next_rating_id().get();
         self.state.next_rating_id_mut().set(rating_id + 1);
 
         let agent_rating = AgentRating {
             job_id,
+            agent: agent_owner,
             rater: caller,
             rating,
             review,
@@ -872,6 +2235,23 @@ impl JobMarketplaceContract {
             .insert(&rating_id, agent_rating)
             .expect("Failed to insert rating");
 
+        self.state
+            .rated_jobs_mut()
+            .insert(&job_id, rating_id)
+            .expect("Failed to index rating");
+
+        let mut agent_rating_ids = self.state
+            .agent_ratings()
+            .get(&agent_owner)
+            .await
+            .expect("Failed to get agent ratings")
+            .unwrap_or_default();
+        agent_rating_ids.push(rating_id);
+        self.state
+            .agent_ratings_mut()
+            .insert(&agent_owner, agent_rating_ids)
+            .expect("Failed to index rating");
+
         // Update agent's rating stats
         let mut agent_profile = self.state
             .agents()
@@ -882,12 +2262,124 @@ impl JobMarketplaceContract {
 
         agent_profile.total_rating_points += rating as u64;
         agent_profile.total_ratings += 1;
+        agent_profile.average_rating =
+            agent_profile.total_rating_points as f64 / agent_profile.total_ratings as f64;
+
+        // Weight by payment, growing exponentially with time so that each rating
+        // counts half as much as one given RATING_HALF_LIFE later. Only the ratio
+        // is exposed, so the common growth factor cancels out. Time is measured
+        // from instantiation, keeping the weights within f64 range for centuries.
+        let payment = job.accepted_bid_amount.unwrap_or(job.payment);
+        let tokens = payment.to_attos() as f64 / Amount::ONE.to_attos() as f64;
+        let elapsed = self.runtime.system_time().delta_since(*self.state.rating_epoch().get());
+        let age = elapsed.as_micros() as f64 / RATING_HALF_LIFE.as_micros() as f64;
+        let weight = tokens * age.exp2();
+        agent_profile.weighted_rating_points += weight * rating as f64;
+        agent_profile.weighted_rating_total += weight;
+        if agent_profile.weighted_rating_total > 0.0 {
+            agent_profile.weighted_score =
+                agent_profile.weighted_rating_points / agent_profile.weighted_rating_total;
+        }
 
         self.state
             .agents_mut()
             .insert(&agent_owner, agent_profile)
             .expect("Failed to update agent");
 
+        self.emit_event(JobEvent::AgentRated {
+            job_id,
+            agent: agent_owner,
+            rating,
+        });
+
+        Ok(())
+    }
+
+    /// Rate the client after job completion
+    async fn rate_client(
+        &mut self,
+        job_id: u64,
+        rating: u8,
+        review: String,
+    ) -> Result<(), JobMarketplaceError> {
+        // Validate rating is 1-5
+        if !(1..=5).contains(&rating) {
+            return Err(JobMarketplaceError::InvalidRating);
+        }
+
+        let caller = self.get_caller()?;
+
+        // Get job
+        let job = self.state
+            .jobs()
+            .get(&job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
+
+        // Check if job is completed and still open for ratings
+        if job.status != JobStatus::Completed || job.settled {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        // Check if caller is the assigned agent
+        if job.agent != Some(caller) {
+            return Err(JobMarketplaceError::NotAuthorized);
+        }
+
+        // Each job can only be rated once
+        if self.state
+            .client_ratings()
+            .contains_key(&job_id)
+            .await
+            .expect("Failed to check client ratings")
+        {
+            return Err(JobMarketplaceError::AlreadyRated);
+        }
+
+        let client_rating = ClientRating {
+            job_id,
+            client: job.client,
+            rater: caller,
+            rating,
+            review,
+            timestamp: self.runtime.system_time(),
+        };
+
+        self.state
+            .client_ratings_mut()
+            .insert(&job_id, client_rating)
+            .expect("Failed to insert rating");
+
+        // Update client's rating stats
+        let mut client_profile = self.state
+            .clients()
+            .get(&job.client)
+            .await
+            .expect("Failed to get client")
+            .unwrap_or(ClientProfile {
+                owner: job.client,
+                total_rating_points: 0,
+                total_ratings: 0,
+                average_rating: 0.0,
+            });
+
+        client_profile.total_rating_points += rating as u64;
+        client_profile.total_ratings += 1;
+        client_profile.average_rating =
+            client_profile.total_rating_points as f64 / client_profile.total_ratings as f64;
+
+        self.state
+            .clients_mut()
+            .insert(&job.client, client_profile)
+            .expect("Failed to update client");
+
+        self.emit_event(JobEvent::ClientRated {
+            job_id,
+            client: job.client,
+            rating,
+        });
+
         Ok(())
     }
 
@@ -913,9 +2405,29 @@ impl JobMarketplaceContract {
             return Err(JobMarketplaceError::NotAuthorized);
         }
 
-        // Can only dispute jobs in progress
-        if job.status != JobStatus::InProgress && job.status != JobStatus::PendingApproval {
-            return Err(JobMarketplaceError::InvalidStatus);
+        // Jobs in progress can be disputed by either party; completed jobs only by
+        // the client, within the dispute window and while payment is still held
+        match job.status {
+            JobStatus::InProgress | JobStatus::PendingApproval => {}
+            JobStatus::Completed => {
+                if job.client != caller {
+                    return Err(JobMarketplaceError::NotAuthorized);
+                }
+                let completed_at = job.completed_at.ok_or(JobMarketplaceError::InvalidStatus)?;
+                if self.runtime.system_time() > completed_at.saturating_add(DISPUTE_WINDOW) {
+                    return Err(JobMarketplaceError::DisputeWindowClosed);
+                }
+                let held = self.state
+                    .escrow_balance()
+                    .get(&job_id)
+                    .await
+                    .expect("Failed to get escrow balance")
+                    .unwrap_or(Amount::ZERO);
+                if held == Amount::ZERO {
+                    return Err(JobMarketplaceError::DisputeWindowClosed);
+                }
+            }
+            _ => return Err(JobMarketplaceError::InvalidStatus),
         }
 
         // Check for existing dispute
@@ -951,11 +2463,8 @@ impl JobMarketplaceContract {
             .expect("Failed to create dispute");
 
         // Update job status
-        job.status = JobStatus::Disputed;
-        self.state
-            .jobs_mut()
-            .insert(&job_id, job)
-            .expect("Failed to update job");
+        self.set_job_status(&mut job, JobStatus::Disputed).await;
+        self.save_job(job);
 
         Ok(())
     }
@@ -1004,7 +2513,7 @@ impl JobMarketplaceContract {
         Ok(())
     }
 
-    /// Resolve dispute (admin/arbitrator)
+    /// Resolve dispute and settle the escrow (admin only)
     async fn resolve_dispute(
         &mut self,
         dispute_id: u64,
@@ -1012,8 +2521,8 @@ impl JobMarketplaceContract {
         refund_percentage: Option<u8>,
         notes: String,
     ) -> Result<(), JobMarketplaceError> {
-        let _caller = self.get_caller()?;
-        // In production, would verify caller is arbitrator
+        // Only the marketplace admin arbitrates disputes
+        self.get_admin()?;
 
         let mut dispute = self.state
             .disputes()
@@ -1022,6 +2531,84 @@ impl JobMarketplaceContract {
             .expect("Failed to get dispute")
             .ok_or(JobMarketplaceError::DisputeNotFound)?;
 
+        // Only open disputes can be resolved, and only to a final outcome
+        if dispute.status != DisputeStatus::Open && dispute.status != DisputeStatus::UnderReview {
+            return Err(JobMarketplaceError::InvalidStatus);
+        }
+
+        let mut job = self.state
+            .jobs()
+            .get(&dispute.job_id)
+            .await
+            .expect("Failed to get job")
+            .ok_or(JobMarketplaceError::JobNotFound(dispute.job_id))?;
+        let job_id = job.id;
+        let agent_owner = job.agent.ok_or(JobMarketplaceError::AgentNotRegistered)?;
+
+        let held = self.state
+            .escrow_balance()
+            .get(&job_id)
+            .await
+            .expect("Failed to get escrow balance")
+            .unwrap_or(Amount::ZERO);
+
+        // Settle the held escrow according to the resolution
+        let escrow_status = match resolution {
+            DisputeStatus::ResolvedForClient => {
+                self.refund_remaining_escrow(job_id, job.client).await?;
+                self.set_job_status(&mut job, JobStatus::Cancelled).await;
+                EscrowStatus::Refunded
+            }
+            DisputeStatus::ResolvedForAgent => {
+                if held > Amount::ZERO {
+                    self.pay_agent_from_escrow(job_id, agent_owner, held).await?;
+                }
+                self.set_job_status(&mut job, JobStatus::Completed).await;
+                EscrowStatus::Released
+            }
+            DisputeStatus::ResolvedSplit => {
+                let percentage = refund_percentage
+                    .filter(|p| *p <= 100)
+                    .ok_or(JobMarketplaceError::InvalidAmount)? as u128;
+                let attos = held.to_attos();
+                let refund = Amount::from_attos(attos / 100 * percentage + attos % 100 * percentage / 100);
+                let agent_amount = held.try_sub(refund).map_err(|_| JobMarketplaceError::InvalidAmount)?;
+                if refund > Amount::ZERO {
+                    self.transfer_from_escrow(job_id, job.client, refund).await?;
+                }
+                if agent_amount > Amount::ZERO {
+                    self.pay_agent_from_escrow(job_id, agent_owner, agent_amount).await?;
+                }
+                self.set_job_status(&mut job, JobStatus::Completed).await;
+                EscrowStatus::PartiallyRefunded
+            }
+            _ => return Err(JobMarketplaceError::InvalidStatus),
+        };
+        self.settle_escrow_info(&job, escrow_status).await;
+
+        // The agent's stake is forfeited to the client if they lose the dispute
+        let stake_recipient = if resolution == DisputeStatus::ResolvedForClient {
+            job.client
+        } else {
+            agent_owner
+        };
+        self.settle_stake(job_id, agent_owner, stake_recipient).await;
+
+        // Jobs disputed before completion end here: the client and agent are
+        // freed up, and the agent is credited with the job if they get paid
+        if job.completed_at.is_none() {
+            self.close_client_job(job.client).await;
+            self.release_active_job(agent_owner).await;
+            if job.status == JobStatus::Completed {
+                job.completed_at = Some(self.runtime.system_time());
+                self.record_job_completed(agent_owner, job_id).await;
+            }
+        } else if resolution == DisputeStatus::ResolvedForClient {
+            // A completed job refunded to the client no longer counts as completed
+            job.completed_at = None;
+            self.revoke_job_completed(agent_owner, job_id).await;
+        }
+
         dispute.status = resolution;
         dispute.resolved_at = Some(self.runtime.system_time());
         dispute.resolution_notes = Some(notes);
@@ -1029,40 +2616,85 @@ impl JobMarketplaceContract {
 
         self.state
             .disputes_mut()
-            .insert(&dispute_id, dispute.clone())
+            .insert(&dispute_id, dispute)
             .expect("Failed to update dispute");
 
-        // Update job and escrow based on resolution
+        // Update agent dispute stats
+        if let Some(mut agent_profile) = self.state.agents().get(&agent_owner).await.expect("Failed to get agent") {
+            agent_profile.disputes_involved += 1;
+            if resolution == DisputeStatus::ResolvedForClient {
+                agent_profile.disputes_lost += 1;
+            }
+            self.state.agents_mut().insert(&agent_owner, agent_profile).expect("Failed to update agent");
+        }
+
+        self.save_job(job);
+
+        Ok(())
+    }
+
+    // ==================== MODERATION OPERATIONS ====================
+
+    /// Delete a job, returning escrow to the client and stakes to the bidders
+    async fn admin_remove_job(&mut self, job_id: u64) -> Result<(), JobMarketplaceError> {
+        self.get_admin()?;
+
         let mut job = self.state
             .jobs()
-            .get(&dispute.job_id)
+            .get(&job_id)
             .await
             .expect("Failed to get job")
-            .ok_or(JobMarketplaceError::JobNotFound(dispute.job_id))?;
+            .ok_or(JobMarketplaceError::JobNotFound(job_id))?;
 
-        if let Some(escrow_id) = job.escrow_id {
-            if let Some(mut escrow) = self.state.escrow().get(&escrow_id).await.expect("Failed to get escrow") {
-                match resolution {
-                    DisputeStatus::ResolvedForClient => {
-                        escrow.status = EscrowStatus::Refunded;
-                        job.status = JobStatus::Cancelled;
-                    }
-                    DisputeStatus::ResolvedForAgent => {
-                        escrow.status = EscrowStatus::Released;
-                        job.status = JobStatus::Completed;
-                    }
-                    DisputeStatus::ResolvedSplit => {
-                        escrow.status = EscrowStatus::PartiallyRefunded;
-                        job.status = JobStatus::Completed;
-                    }
-                    _ => {}
-                }
-                escrow.released_at = Some(self.runtime.system_time());
-                self.state.escrow_mut().insert(&escrow_id, escrow).expect("Failed to update escrow");
+        self.refund_remaining_escrow(job_id, job.client).await?;
+        self.refund_bid_stakes(&mut job, None).await;
+        if job.status == JobStatus::Posted {
+            self.close_bidding(&job).await;
+        }
+        if job.escrow_id.is_some() {
+            self.settle_escrow_info(&job, EscrowStatus::Refunded).await;
+        }
+
+        // Free up the client and agent if the job was still running
+        let unfinished = job.completed_at.is_none()
+            && matches!(
+                job.status,
+                JobStatus::Posted
+                    | JobStatus::AwaitingConfirmation
+                    | JobStatus::InProgress
+                    | JobStatus::PendingApproval
+                    | JobStatus::Disputed
+            );
+        if unfinished {
+            self.close_client_job(job.client).await;
+            if let Some(agent_owner) = job.agent {
+                self.release_active_job(agent_owner).await;
             }
         }
 
-        self.state.jobs_mut().insert(&dispute.job_id, job).expect("Failed to update job");
+        self.adjust_status_count(job.status, -1).await;
+        let total_bids = self.state.total_bids().get().saturating_sub(job.bids.len() as u64);
+        self.state.total_bids_mut().set(total_bids);
+
+        self.state
+            .jobs_mut()
+            .remove(&job_id)
+            .expect("Failed to remove job");
+
+        Ok(())
+    }
+
+    /// Ban an agent from bidding on jobs or registering again
+    async fn ban_agent(&mut self, agent: AccountOwner) -> Result<(), JobMarketplaceError> {
+        self.get_admin()?;
+
+        self.state
+            .banned_mut()
+            .insert(&agent, true)
+            .expect("Failed to ban agent");
+
+        // Banned agents can no longer win open jobs, so their bids go too
+        self.withdraw_open_bids(agent).await;
 
         Ok(())
     }
@@ -1132,3 +2764,7 @@ impl JobMarketplaceContract {
         Ok(())
     }
 }
+
+#[cfg(test)]
+#[path = "unit_tests/contract.rs"]
+mod tests;
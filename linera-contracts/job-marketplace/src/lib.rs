@@ -12,10 +12,10 @@ A decentralized job marketplace on Linera with:
 - In-app messaging
 */
 
-use async_graphql::{Enum, Request, Response, SimpleObject, InputObject};
+use async_graphql::{ComplexObject, Enum, Request, Response, SimpleObject, InputObject};
 use linera_sdk::{
     graphql::GraphQLMutationRoot,
-    linera_base_types::{AccountOwner, Amount, Timestamp},
+    linera_base_types::{AccountOwner, Amount, ChainId, TimeDelta, Timestamp},
     views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext},
 };
 use serde::{Deserialize, Serialize};
@@ -31,14 +31,46 @@ pub struct JobMarketplace {
     jobs: MapView<u64, Job>,
     /// Agent profiles
     agents: MapView<AccountOwner, AgentProfile>,
+    /// Agents banned by the admin from bidding or registering
+    banned: MapView<AccountOwner, bool>,
+    /// Most recent completed jobs per agent, newest last (capped at MAX_AGENT_JOB_HISTORY)
+    agent_jobs: MapView<AccountOwner, Vec<u64>>,
     /// Agent ratings/reviews
     ratings: MapView<u64, AgentRating>,
+    /// Rated jobs index (job_id -> rating_id)
+    rated_jobs: MapView<u64, u64>,
+    /// Ratings received by each agent, oldest first (agent -> rating_ids)
+    agent_ratings: MapView<AccountOwner, Vec<u64>>,
+    /// Client profiles built from ratings given by agents
+    clients: MapView<AccountOwner, ClientProfile>,
+    /// Number of unfinished jobs per client
+    open_jobs: MapView<AccountOwner, u64>,
+    /// Jobs bookmarked by each user (capped at MAX_FAVORITES)
+    favorites: MapView<AccountOwner, Vec<u64>>,
+    /// Client ratings/reviews by agents (job_id -> rating)
+    client_ratings: MapView<u64, ClientRating>,
     /// Escrow balances (job_id -> locked amount)
     escrow: MapView<u64, EscrowInfo>,
+    /// Funds actually held by the application per job (job_id -> amount)
+    escrow_balance: MapView<u64, Amount>,
+    /// Stakes held for bids ((job_id, agent) -> amount)
+    bid_stakes: MapView<(u64, AccountOwner), Amount>,
+    /// Jobs still open for bidding on which each agent has a bid (agent -> job_ids)
+    open_bids: MapView<AccountOwner, Vec<u64>>,
     /// Disputes
     disputes: MapView<u64, Dispute>,
     /// Messages between users
     messages: MapView<u64, ChatMessage>,
+    /// Jobs announced by other chains (populated on the job board chain)
+    remote_jobs: MapView<(ChainId, u64), RemoteJob>,
+    /// Number of jobs in each status, maintained on every transition
+    status_counts: MapView<JobStatus, u64>,
+    /// Number of bids currently placed across all jobs
+    total_bids: RegisterView<u64>,
+    /// Sum of the payments of all jobs ever posted
+    total_payment_volume: RegisterView<Amount>,
+    /// Set once the payment volume no longer fits in an Amount
+    payment_volume_overflowed: RegisterView<bool>,
     /// Next job ID
     next_job_id: RegisterView<u64>,
     /// Next rating ID
@@ -47,6 +79,12 @@ pub struct JobMarketplace {
     next_dispute_id: RegisterView<u64>,
     /// Next message ID
     next_message_id: RegisterView<u64>,
+    /// Marketplace administrator
+    admin: RegisterView<Option<AccountOwner>>,
+    /// Minimum payment accepted for a job
+    min_job_payment: RegisterView<Amount>,
+    /// Instantiation time, which rating ages are measured from
+    rating_epoch: RegisterView<Timestamp>,
 }
 
 impl JobMarketplace {
@@ -66,6 +104,22 @@ impl JobMarketplace {
         &mut self.agents
     }
 
+    pub fn banned(&self) -> &MapView<AccountOwner, bool> {
+        &self.banned
+    }
+
+    pub fn banned_mut(&mut self) -> &mut MapView<AccountOwner, bool> {
+        &mut self.banned
+    }
+
+    pub fn agent_jobs(&self) -> &MapView<AccountOwner, Vec<u64>> {
+        &self.agent_jobs
+    }
+
+    pub fn agent_jobs_mut(&mut self) -> &mut MapView<AccountOwner, Vec<u64>> {
+        &mut self.agent_jobs
+    }
+
     pub fn ratings(&self) -> &MapView<u64, AgentRating> {
         &self.ratings
     }
@@ -74,6 +128,54 @@ impl JobMarketplace {
         &mut self.ratings
     }
 
+    pub fn rated_jobs(&self) -> &MapView<u64, u64> {
+        &self.rated_jobs
+    }
+
+    pub fn rated_jobs_mut(&mut self) -> &mut MapView<u64, u64> {
+        &mut self.rated_jobs
+    }
+
+    pub fn agent_ratings(&self) -> &MapView<AccountOwner, Vec<u64>> {
+        &self.agent_ratings
+    }
+
+    pub fn agent_ratings_mut(&mut self) -> &mut MapView<AccountOwner, Vec<u64>> {
+        &mut self.agent_ratings
+    }
+
+    pub fn clients(&self) -> &MapView<AccountOwner, ClientProfile> {
+        &self.clients
+    }
+
+    pub fn clients_mut(&mut self) -> &mut MapView<AccountOwner, ClientProfile> {
+        &mut self.clients
+    }
+
+    pub fn open_jobs(&self) -> &MapView<AccountOwner, u64> {
+        &self.open_jobs
+    }
+
+    pub fn open_jobs_mut(&mut self) -> &mut MapView<AccountOwner, u64> {
+        &mut self.open_jobs
+    }
+
+    pub fn favorites(&self) -> &MapView<AccountOwner, Vec<u64>> {
+        &self.favorites
+    }
+
+    pub fn favorites_mut(&mut self) -> &mut MapView<AccountOwner, Vec<u64>> {
+        &mut self.favorites
+    }
+
+    pub fn client_ratings(&self) -> &MapView<u64, ClientRating> {
+        &self.client_ratings
+    }
+
+    pub fn client_ratings_mut(&mut self) -> &mut MapView<u64, ClientRating> {
+        &mut self.client_ratings
+    }
+
     pub fn escrow(&self) -> &MapView<u64, EscrowInfo> {
         &self.escrow
     }
@@ -82,6 +184,30 @@ impl JobMarketplace {
         &mut self.escrow
     }
 
+    pub fn escrow_balance(&self) -> &MapView<u64, Amount> {
+        &self.escrow_balance
+    }
+
+    pub fn escrow_balance_mut(&mut self) -> &mut MapView<u64, Amount> {
+        &mut self.escrow_balance
+    }
+
+    pub fn bid_stakes(&self) -> &MapView<(u64, AccountOwner), Amount> {
+        &self.bid_stakes
+    }
+
+    pub fn bid_stakes_mut(&mut self) -> &mut MapView<(u64, AccountOwner), Amount> {
+        &mut self.bid_stakes
+    }
+
+    pub fn open_bids(&self) -> &MapView<AccountOwner, Vec<u64>> {
+        &self.open_bids
+    }
+
+    pub fn open_bids_mut(&mut self) -> &mut MapView<AccountOwner, Vec<u64>> {
+        &mut self.open_bids
+    }
+
     pub fn disputes(&self) -> &MapView<u64, Dispute> {
         &self.disputes
     }
@@ -98,6 +224,46 @@ impl JobMarketplace {
         &mut self.messages
     }
 
+    pub fn remote_jobs(&self) -> &MapView<(ChainId, u64), RemoteJob> {
+        &self.remote_jobs
+    }
+
+    pub fn remote_jobs_mut(&mut self) -> &mut MapView<(ChainId, u64), RemoteJob> {
+        &mut self.remote_jobs
+    }
+
+    pub fn status_counts(&self) -> &MapView<JobStatus, u64> {
+        &self.status_counts
+    }
+
+    pub fn status_counts_mut(&mut self) -> &mut MapView<JobStatus, u64> {
+        &mut self.status_counts
+    }
+
+    pub fn total_bids(&self) -> &RegisterView<u64> {
+        &self.total_bids
+    }
+
+    pub fn total_bids_mut(&mut self) -> &mut RegisterView<u64> {
+        &mut self.total_bids
+    }
+
+    pub fn total_payment_volume(&self) -> &RegisterView<Amount> {
+        &self.total_payment_volume
+    }
+
+    pub fn total_payment_volume_mut(&mut self) -> &mut RegisterView<Amount> {
+        &mut self.total_payment_volume
+    }
+
+    pub fn payment_volume_overflowed(&self) -> &RegisterView<bool> {
+        &self.payment_volume_overflowed
+    }
+
+    pub fn payment_volume_overflowed_mut(&mut self) -> &mut RegisterView<bool> {
+        &mut self.payment_volume_overflowed
+    }
+
     pub fn next_job_id(&self) -> &RegisterView<u64> {
         &self.next_job_id
     }
@@ -129,6 +295,30 @@ impl JobMarketplace {
     pub fn next_message_id_mut(&mut self) -> &mut RegisterView<u64> {
         &mut self.next_message_id
     }
+
+    pub fn admin(&self) -> &RegisterView<Option<AccountOwner>> {
+        &self.admin
+    }
+
+    pub fn admin_mut(&mut self) -> &mut RegisterView<Option<AccountOwner>> {
+        &mut self.admin
+    }
+
+    pub fn min_job_payment(&self) -> &RegisterView<Amount> {
+        &self.min_job_payment
+    }
+
+    pub fn min_job_payment_mut(&mut self) -> &mut RegisterView<Amount> {
+        &mut self.min_job_payment
+    }
+
+    pub fn rating_epoch(&self) -> &RegisterView<Timestamp> {
+        &self.rating_epoch
+    }
+
+    pub fn rating_epoch_mut(&mut self) -> &mut RegisterView<Timestamp> {
+        &mut self.rating_epoch
+    }
 }
 
 // ==================== ENUMS ====================
@@ -138,6 +328,8 @@ impl JobMarketplace {
 pub enum JobStatus {
     /// Job is open for bids
     Posted,
+    /// Bid accepted, waiting for the agent to confirm
+    AwaitingConfirmation,
     /// Bid accepted, work in progress
     InProgress,
     /// Agent submitted work, awaiting client approval
@@ -146,6 +338,8 @@ pub enum JobStatus {
     Completed,
     /// Job was cancelled
     Cancelled,
+    /// Deadline passed before any bid was accepted
+    Expired,
     /// In dispute resolution
     Disputed,
 }
@@ -234,6 +428,15 @@ pub struct Job {
     pub milestones: Vec<Milestone>,
     pub accepted_bid_amount: Option<Amount>,
     pub escrow_id: Option<u64>,
+    pub completed_at: Option<Timestamp>,
+    /// When bidding closes and the lowest bid can be accepted automatically
+    pub auto_accept_at: Option<Timestamp>,
+    /// When the job was posted or last changed in any way (status, bids, edits, milestones)
+    pub updated_at: Timestamp,
+    /// When the current agent's bid was accepted
+    pub accepted_at: Option<Timestamp>,
+    /// Closed by the client: escrow swept and no further ratings accepted
+    pub settled: bool,
 }
 
 /// A bid on a job with amount
@@ -246,6 +449,8 @@ pub struct Bid {
     pub amount: Amount,
     pub proposal: String,
     pub estimated_days: u32,
+    /// Deposit held while the bid is open or the job is in progress
+    pub stake: Amount,
 }
 
 /// Job milestone for phased delivery
@@ -257,10 +462,13 @@ pub struct Milestone {
     pub payment_percentage: u8, // % of total payment
     pub status: MilestoneStatus,
     pub due_date: Option<Timestamp>,
+    /// Whether this milestone's share has been paid out to the agent
+    pub released: bool,
 }
 
 /// Agent profile with verification
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
 pub struct AgentProfile {
     pub owner: AccountOwner,
     pub name: String,
@@ -268,6 +476,18 @@ pub struct AgentProfile {
     pub jobs_completed: u64,
     pub total_rating_points: u64,
     pub total_ratings: u64,
+    #[serde(with = "f64_bits")]
+    pub average_rating: f64, // Cached total_rating_points / total_ratings
+    /// Rating sum weighted by job payment and recency (see RATING_HALF_LIFE)
+    #[graphql(skip)]
+    #[serde(with = "f64_bits")]
+    pub weighted_rating_points: f64,
+    /// Sum of the weights behind `weighted_rating_points`
+    #[graphql(skip)]
+    #[serde(with = "f64_bits")]
+    pub weighted_rating_total: f64,
+    #[serde(with = "f64_bits")]
+    pub weighted_score: f64, // Cached weighted_rating_points / weighted_rating_total
     pub registered_at: Timestamp,
     // New fields
     pub verification_level: VerificationLevel,
@@ -277,18 +497,69 @@ pub struct AgentProfile {
     pub availability: bool,
     pub response_time_hours: u32,
     pub success_rate: u8, // Percentage 0-100
+    pub active_jobs: u64, // Assigned jobs not yet completed or cancelled
+    // Dispute tracking
+    pub disputes_involved: u64,
+    pub disputes_lost: u64,
+}
+
+#[ComplexObject]
+impl AgentProfile {
+    /// Share of the agent's finished jobs that went through a dispute (0.0-1.0).
+    /// A disputed job either ends completed or counts as lost, never both.
+    async fn dispute_rate(&self) -> f64 {
+        let finished = self.jobs_completed + self.disputes_lost;
+        if finished == 0 {
+            0.0
+        } else {
+            self.disputes_involved as f64 / finished as f64
+        }
+    }
 }
 
 /// Agent rating/review
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
 pub struct AgentRating {
     pub job_id: u64,
+    pub agent: AccountOwner,
+    pub rater: AccountOwner,
+    pub rating: u8, // 1-5 stars
+    pub review: String,
+    pub timestamp: Timestamp,
+}
+
+/// Client reputation, built from ratings given by agents
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct ClientProfile {
+    pub owner: AccountOwner,
+    pub total_rating_points: u64,
+    pub total_ratings: u64,
+    #[serde(with = "f64_bits")]
+    pub average_rating: f64, // Cached total_rating_points / total_ratings
+}
+
+/// Client rating/review by the agent who did the job
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct ClientRating {
+    pub job_id: u64,
+    pub client: AccountOwner,
     pub rater: AccountOwner,
     pub rating: u8, // 1-5 stars
     pub review: String,
     pub timestamp: Timestamp,
 }
 
+/// Job posted on another chain and announced to the job board
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct RemoteJob {
+    pub origin_chain_id: ChainId,
+    pub job_id: u64,
+    pub title: String,
+    pub payment: Amount,
+    pub category: JobCategory,
+    pub received_at: Timestamp,
+}
+
 /// Escrow information for a job
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
 pub struct EscrowInfo {
@@ -327,130 +598,320 @@ pub struct ChatMessage {
     pub read: bool,
 }
 
+/// Stores an `f64` as its bit pattern, since BCS has no floating-point encoding
+mod f64_bits {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(value.to_bits())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        u64::deserialize(deserializer).map(f64::from_bits)
+    }
+}
+
+// ==================== CONSTANTS ====================
+
+/// How long a client may dispute a completed job before payment can be released
+pub const DISPUTE_WINDOW: TimeDelta = TimeDelta::from_secs(3 * 24 * 60 * 60);
+
+/// Maximum number of tags on a single job
+pub const MAX_TAGS_PER_JOB: usize = 10;
+
+/// Ratings an agent needs before ranking on rating-based leaderboards
+pub const MIN_RATINGS_FOR_RANKING: u64 = 3;
+
+/// Age after which a rating counts half as much toward an agent's weighted score
+pub const RATING_HALF_LIFE: TimeDelta = TimeDelta::from_secs(90 * 24 * 60 * 60);
+
+/// Maximum length of a bid proposal, in characters
+pub const MAX_PROPOSAL_LENGTH: usize = 2000;
+
+/// Maximum length of a job title or agent name, in characters
+pub const MAX_NAME_LENGTH: usize = 128;
+
+/// Maximum length of a job or agent service description, in characters
+pub const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+/// Number of completed jobs kept in each agent's history
+pub const MAX_AGENT_JOB_HISTORY: usize = 100;
+
+/// Maximum number of jobs a user can bookmark
+pub const MAX_FAVORITES: usize = 100;
+
+// ==================== PARAMETERS ====================
+
+/// Basis points in 100%
+pub const MAX_FEE_BASIS_POINTS: u16 = 10_000;
+
+/// Application parameters, fixed when the application is created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceParameters {
+    /// Platform fee taken from each payout, in basis points (250 = 2.5%)
+    pub fee_basis_points: u16,
+    /// Account receiving the platform fee
+    pub fee_recipient: AccountOwner,
+    /// Chain aggregating job postings from every client chain, if any
+    #[serde(default)]
+    pub job_board_chain_id: Option<ChainId>,
+    /// Maximum number of unfinished jobs a client may have at once (unlimited if unset)
+    #[serde(default)]
+    pub max_open_jobs_per_client: Option<u64>,
+    /// Minimum stake an agent must deposit with each bid
+    #[serde(default)]
+    pub min_bid_stake: Amount,
+    /// Maximum number of jobs an agent may work on at once (unlimited if unset)
+    #[serde(default)]
+    pub max_active_jobs_per_agent: Option<u64>,
+    /// How long after acceptance a client may reassign an unfinished job, in seconds
+    #[serde(default = "default_reassign_after_secs")]
+    pub reassign_after_secs: u64,
+    /// How long an agent has to confirm an accepted bid, in seconds
+    #[serde(default = "default_confirm_within_secs")]
+    pub confirm_within_secs: u64,
+}
+
+fn default_reassign_after_secs() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+fn default_confirm_within_secs() -> u64 {
+    2 * 24 * 60 * 60
+}
+
+/// Argument passed when the application is instantiated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstantiationArgument {
+    /// Marketplace administrator (moderation, dispute resolution)
+    pub admin: AccountOwner,
+    /// Minimum payment accepted for a job
+    pub min_job_payment: Amount,
+}
+
 // ==================== OPERATIONS ====================
 
-/// Operations that can be performed
-#[derive(Debug, Serialize, Deserialize, GraphQLMutationRoot)]
-pub enum Operation {
-    // ===== Job Operations =====
-    /// Post a new job with escrow
-    PostJob {
-        title: String,
-        description: String,
-        payment: Amount,
-        category: JobCategory,
-        tags: Vec<String>,
-        deadline: Option<u64>, // Unix timestamp
-        milestones: Vec<MilestoneInput>,
-    },
-    /// Cancel a posted job (only if no accepted bid)
-    CancelJob {
-        job_id: u64,
-    },
+pub use operations::Operation;
+
+// The `GraphQLMutationRoot` derive turns every variant into a mutation taking each
+// field as an argument, so the lint is allowed for the generated code only
+#[allow(clippy::too_many_arguments)]
+mod operations {
+    use super::*;
+
+    /// Operations that can be performed
+    #[derive(Debug, Serialize, Deserialize, GraphQLMutationRoot)]
+    pub enum Operation {
+        // ===== Job Operations =====
+        /// Post a new job with escrow
+        PostJob {
+            title: String,
+            description: String,
+            payment: Amount,
+            category: JobCategory,
+            tags: Vec<String>,
+            deadline: Option<u64>, // Unix timestamp
+            milestones: Vec<MilestoneInput>,
+            auto_accept_at: Option<u64>, // Unix timestamp
+        },
+        /// Edit a posted job's description or payment (only if no accepted bid).
+        /// Raising the payment locks the difference in escrow; lowering it refunds it.
+        EditJob {
+            job_id: u64,
+            description: Option<String>,
+            payment: Option<Amount>,
+        },
+        /// Cancel a posted job (only if no accepted bid)
+        CancelJob {
+            job_id: u64,
+        },
+        /// Expire a posted job past its deadline (callable by anyone, refunds escrow)
+        ExpireJob {
+            job_id: u64,
+        },
+        /// Bookmark a job
+        AddFavorite {
+            job_id: u64,
+        },
+        /// Remove a bookmarked job
+        RemoveFavorite {
+            job_id: u64,
+        },
     
-    // ===== Bidding Operations =====
-    /// Place a bid on a job with amount and proposal
-    PlaceBid {
-        job_id: u64,
-        amount: Amount,
-        proposal: String,
-        estimated_days: u32,
-    },
-    /// Withdraw a bid
-    WithdrawBid {
-        job_id: u64,
-    },
-    /// Accept a bid (locks payment in escrow)
-    AcceptBid {
-        job_id: u64,
-        agent: AccountOwner,
-        bid_amount: Amount,
-    },
+        // ===== Bidding Operations =====
+        /// Place a bid on a job with amount and proposal, staking a deposit.
+        /// An agent holds at most one bid per job; withdraw it first to re-bid.
+        /// The stake is returned when the bid is withdrawn or rejected, or once the
+        /// job's payment is released, and is paid to the client if the job is abandoned.
+        PlaceBid {
+            job_id: u64,
+            amount: Amount,
+            proposal: String,
+            estimated_days: u32,
+            stake: Amount,
+        },
+        /// Withdraw a bid
+        WithdrawBid {
+            job_id: u64,
+        },
+        /// Accept a bid (locks payment in escrow)
+        AcceptBid {
+            job_id: u64,
+            agent: AccountOwner,
+            bid_amount: Amount,
+        },
+        /// Accept the lowest bid once a job's auto-accept time has passed (callable by anyone)
+        FinalizeAuction {
+            job_id: u64,
+        },
+        /// Hand an in-progress job to another bidder once the reassignment period has
+        /// passed (client only; the previous agent's stake goes to the client)
+        ReassignJob {
+            job_id: u64,
+            new_agent: AccountOwner,
+        },
+        /// Confirm an accepted bid and start working on the job (agent only).
+        /// An agent without a stake on the job, after a reassignment or reopening,
+        /// locks the marketplace minimum stake first.
+        ConfirmJob {
+            job_id: u64,
+        },
+        /// Reopen a job for bidding when the agent did not confirm in time
+        /// (client only; the agent's stake goes to the client)
+        ReopenJob {
+            job_id: u64,
+        },
     
-    // ===== Work Delivery Operations =====
-    /// Submit work for a milestone
-    SubmitMilestone {
-        job_id: u64,
-        milestone_id: u64,
-        delivery_notes: String,
-    },
-    /// Approve a milestone (releases proportional payment)
-    ApproveMilestone {
-        job_id: u64,
-        milestone_id: u64,
-    },
-    /// Request revision for a milestone
-    RequestRevision {
-        job_id: u64,
-        milestone_id: u64,
-        feedback: String,
-    },
-    /// Complete entire job (releases remaining payment)
-    CompleteJob {
-        job_id: u64,
-    },
+        // ===== Work Delivery Operations =====
+        /// Submit work for a milestone
+        SubmitMilestone {
+            job_id: u64,
+            milestone_id: u64,
+            delivery_notes: String,
+        },
+        /// Approve a submitted milestone so its payment can be released
+        ApproveMilestone {
+            job_id: u64,
+            milestone_id: u64,
+        },
+        /// Pay an approved milestone's share of the escrow to the agent (client only).
+        /// The job completes once every milestone has been released.
+        ReleaseMilestone {
+            job_id: u64,
+            milestone_id: u64,
+        },
+        /// Request revision for a milestone
+        RequestRevision {
+            job_id: u64,
+            milestone_id: u64,
+            feedback: String,
+        },
+        /// Complete entire job (payment is held until the dispute window closes)
+        CompleteJob {
+            job_id: u64,
+        },
+        /// Release a completed job's escrow to the agent once the dispute window has closed
+        ReleasePayment {
+            job_id: u64,
+        },
+        /// Close a completed job once the dispute window has passed (client only):
+        /// pays any unreleased payment to the agent, returns leftovers to the
+        /// client and stops further ratings
+        SettleJob {
+            job_id: u64,
+        },
+        /// Cancel an in-progress job whose deadline passed without completion
+        /// (client only; refunds the escrow and pays the agent's stake to the client)
+        AbandonJob {
+            job_id: u64,
+        },
     
-    // ===== Agent Operations =====
-    /// Register as an agent with enhanced profile
-    RegisterAgent {
-        name: String,
-        service_description: String,
-        skills: Vec<String>,
-        hourly_rate: Option<Amount>,
-    },
-    /// Update agent profile
-    UpdateAgentProfile {
-        name: Option<String>,
-        service_description: Option<String>,
-        skills: Option<Vec<String>>,
-        portfolio_urls: Option<Vec<String>>,
-        hourly_rate: Option<Amount>,
-        availability: Option<bool>,
-    },
-    /// Request verification upgrade
-    RequestVerification {
-        level: VerificationLevel,
-        proof_data: String,
-    },
+        // ===== Agent Operations =====
+        /// Register as an agent with enhanced profile
+        RegisterAgent {
+            name: String,
+            service_description: String,
+            skills: Vec<String>,
+            hourly_rate: Option<Amount>,
+        },
+        /// Update agent profile
+        UpdateAgentProfile {
+            name: Option<String>,
+            service_description: Option<String>,
+            skills: Option<Vec<String>>,
+            portfolio_urls: Option<Vec<String>>,
+            hourly_rate: Option<Amount>,
+            availability: Option<bool>,
+        },
+        /// Mark the caller as available for (or busy with) new work
+        SetAvailability {
+            available: bool,
+        },
+        /// Leave the marketplace (only with no active jobs), withdrawing open bids and
+        /// returning their stakes; re-registering starts a fresh profile and job history
+        DeregisterAgent,
+        /// Request verification upgrade
+        RequestVerification {
+            level: VerificationLevel,
+            proof_data: String,
+        },
     
-    // ===== Rating Operations =====
-    /// Rate an agent after job completion
-    RateAgent {
-        job_id: u64,
-        rating: u8,
-        review: String,
-    },
+        // ===== Rating Operations =====
+        /// Rate an agent after job completion
+        RateAgent {
+            job_id: u64,
+            rating: u8,
+            review: String,
+        },
+        /// Rate the client after job completion (assigned agent only)
+        RateClient {
+            job_id: u64,
+            rating: u8,
+            review: String,
+        },
     
-    // ===== Dispute Operations =====
-    /// Open a dispute
-    OpenDispute {
-        job_id: u64,
-        reason: String,
-    },
-    /// Respond to a dispute
-    RespondToDispute {
-        dispute_id: u64,
-        response: String,
-    },
-    /// Resolve dispute (admin/arbitrator only)
-    ResolveDispute {
-        dispute_id: u64,
-        resolution: DisputeStatus,
-        refund_percentage: Option<u8>,
-        notes: String,
-    },
+        // ===== Dispute Operations =====
+        /// Open a dispute on a job in progress, or on a completed job within the dispute window
+        OpenDispute {
+            job_id: u64,
+            reason: String,
+        },
+        /// Respond to a dispute
+        RespondToDispute {
+            dispute_id: u64,
+            response: String,
+        },
+        /// Resolve dispute and settle the escrow (admin only)
+        ResolveDispute {
+            dispute_id: u64,
+            resolution: DisputeStatus,
+            refund_percentage: Option<u8>,
+            notes: String,
+        },
     
-    // ===== Messaging Operations =====
-    /// Send a message
-    SendMessage {
-        job_id: u64,
-        recipient: AccountOwner,
-        content: String,
-    },
-    /// Mark messages as read
-    MarkMessagesRead {
-        message_ids: Vec<u64>,
-    },
+        // ===== Moderation Operations =====
+        /// Delete a job, refunding its escrow and stakes (admin only)
+        AdminRemoveJob {
+            job_id: u64,
+        },
+        /// Ban an agent from bidding or registering, withdrawing their open bids
+        /// and returning the stakes (admin only)
+        BanAgent {
+            agent: AccountOwner,
+        },
+    
+        // ===== Messaging Operations =====
+        /// Send a message
+        SendMessage {
+            job_id: u64,
+            recipient: AccountOwner,
+            content: String,
+        },
+        /// Mark messages as read
+        MarkMessagesRead {
+            message_ids: Vec<u64>,
+        },
+    }
 }
 
 /// Input for milestone creation
@@ -505,6 +966,65 @@ pub enum Message {
     },
 }
 
+// ==================== EVENTS ====================
+
+/// Name of the stream job lifecycle events are emitted on
+pub const JOB_EVENTS_STREAM: &str = "job_events";
+
+/// Job lifecycle events for off-chain indexers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JobEvent {
+    /// A job was posted with its payment locked in escrow
+    JobPosted {
+        job_id: u64,
+        client: AccountOwner,
+        payment: Amount,
+    },
+    /// An agent placed a bid on a job
+    BidPlaced {
+        job_id: u64,
+        agent: AccountOwner,
+        amount: Amount,
+    },
+    /// The client accepted an agent's bid
+    BidAccepted {
+        job_id: u64,
+        agent: AccountOwner,
+        amount: Amount,
+    },
+    /// The client accepted the finished work; the payment stays in escrow
+    /// until the dispute window closes
+    JobCompleted {
+        job_id: u64,
+        agent: AccountOwner,
+        amount: Amount,
+    },
+    /// Escrowed payment for a job was paid out to the agent, net of the platform fee
+    PaymentReleased {
+        job_id: u64,
+        agent: AccountOwner,
+        amount: Amount,
+        fee: Amount,
+    },
+    /// The client rated the agent for a job
+    AgentRated {
+        job_id: u64,
+        agent: AccountOwner,
+        rating: u8,
+    },
+    /// The agent rated the client for a job
+    ClientRated {
+        job_id: u64,
+        client: AccountOwner,
+        rating: u8,
+    },
+    /// A job moved to a new status
+    JobStatusChanged {
+        job_id: u64,
+        status: JobStatus,
+    },
+}
+
 // ==================== ERRORS ====================
 
 /// Application errors
@@ -534,6 +1054,15 @@ pub enum JobMarketplaceError {
     #[error("Agent already registered")]
     AgentAlreadyRegistered,
     
+    #[error("Agent has active jobs")]
+    HasActiveJobs,
+    
+    #[error("Too many open jobs")]
+    TooManyOpenJobs,
+    
+    #[error("Bid stake below the marketplace minimum")]
+    StakeTooLow,
+    
     #[error("Bid not found")]
     BidNotFound,
     
@@ -549,9 +1078,36 @@ pub enum JobMarketplaceError {
     #[error("Dispute not found")]
     DisputeNotFound,
     
+    #[error("Agent is banned")]
+    AgentBanned,
+    
+    #[error("Too many favorites (max {MAX_FAVORITES})")]
+    TooManyFavorites,
+    
+    #[error("Job cannot be reassigned yet")]
+    ReassignTooEarly,
+    
+    #[error("Agent can still confirm the job")]
+    ConfirmationPending,
+    
+    #[error("Input too long")]
+    InputTooLong,
+    
+    #[error("Agent is at capacity")]
+    AgentAtCapacity,
+    
+    #[error("Invalid input")]
+    InvalidInput,
+    
     #[error("Dispute already open")]
     DisputeAlreadyOpen,
     
+    #[error("Dispute window still open")]
+    DisputeWindowOpen,
+    
+    #[error("Dispute window closed")]
+    DisputeWindowClosed,
+    
     #[error("Milestone not found")]
     MilestoneNotFound,
     
@@ -561,21 +1117,47 @@ pub enum JobMarketplaceError {
     #[error("Job deadline passed")]
     DeadlinePassed,
     
+    #[error("Job deadline not reached")]
+    DeadlineNotReached,
+    
+    #[error("Deadline is too far in the future")]
+    InvalidDeadline,
+    
     #[error("Message not found")]
     MessageNotFound,
     
     #[error("Invalid amount")]
     InvalidAmount,
+    
+    #[error("Payment below the marketplace minimum")]
+    PaymentTooLow,
+    
+    #[error("Too many tags (max {MAX_TAGS_PER_JOB})")]
+    TooManyTags,
+    
+    #[error("Proposal too long (max {MAX_PROPOSAL_LENGTH} characters)")]
+    ProposalTooLong,
 }
 
 // ==================== ABI ====================
 
+/// Successful outcome of an operation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OperationResponse {
+    /// Operation completed with nothing to report
+    Ok,
+    /// A job was posted under this ID
+    JobPosted { job_id: u64 },
+    /// A bid was placed under this ID
+    BidPlaced { bid_id: u64 },
+}
+
 /// Application ABI
 pub struct JobMarketplaceAbi;
 
 impl linera_sdk::abi::ContractAbi for JobMarketplaceAbi {
     type Operation = Operation;
-    type Response = Result<(), JobMarketplaceError>;
+    type Response = Result<OperationResponse, JobMarketplaceError>;
 }
 
 impl linera_sdk::abi::ServiceAbi for JobMarketplaceAbi {
@@ -12,17 +12,16 @@ Enhanced with queries for:
 
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-mod state;
-
 use std::sync::Arc;
 use async_graphql::{EmptySubscription, Enum, InputObject, Object, Request, Response, Schema};
 use job_marketplace::{
-    AgentProfile, AgentRating, ChatMessage, Dispute, DisputeStatus, EscrowInfo,
-    Job, JobCategory, JobMarketplace, JobStatus, Operation, VerificationLevel,
+    AgentProfile, AgentRating, ChatMessage, ClientProfile, ClientRating, Dispute, DisputeStatus, EscrowInfo,
+    Job, JobCategory, JobMarketplace, JobStatus, MarketplaceParameters, Operation,
+    RemoteJob, VerificationLevel, MIN_RATINGS_FOR_RANKING,
 };
 use linera_sdk::{
     graphql::GraphQLMutationRoot as _,
-    linera_base_types::WithServiceAbi,
+    linera_base_types::{AccountOwner, Amount, Timestamp, WithServiceAbi},
     views::View,
     Service, ServiceRuntime,
 };
@@ -39,7 +38,7 @@ impl WithServiceAbi for JobMarketplaceService {
 }
 
 impl Service for JobMarketplaceService {
-    type Parameters = ();
+    type Parameters = MarketplaceParameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
         let state = JobMarketplace::load(runtime.root_view_storage_context())
@@ -55,6 +54,7 @@ impl Service for JobMarketplaceService {
         let schema = Schema::build(
             QueryRoot {
                 state: self.state.clone(),
+                runtime: self.runtime.clone(),
             },
             Operation::mutation_root(self.runtime.clone()),
             EmptySubscription,
@@ -73,8 +73,10 @@ struct JobFilter {
     status: Option<JobStatus>,
     /// Filter by category
     category: Option<JobCategory>,
-    /// Filter by tags (any match)
+    /// Filter by tags (any match unless `match_all_tags` is set)
     tags: Option<Vec<String>>,
+    /// Require every requested tag to match
+    match_all_tags: Option<bool>,
     /// Minimum payment amount
     min_payment: Option<String>,
     /// Maximum payment amount
@@ -85,6 +87,12 @@ struct JobFilter {
     search: Option<String>,
     /// Has milestones
     has_milestones: Option<bool>,
+    /// Exclude expired jobs, including posted jobs past their deadline
+    exclude_expired: Option<bool>,
+    /// Only jobs created at or after this time
+    created_after: Option<Timestamp>,
+    /// Only jobs created at or before this time
+    created_before: Option<Timestamp>,
 }
 
 /// Sort direction
@@ -117,7 +125,7 @@ struct AgentFilter {
     verification_level: Option<VerificationLevel>,
     /// Filter by skills (any match)
     skills: Option<Vec<String>>,
-    /// Only available agents
+    /// Filter by availability (true hides agents who are busy)
     available: Option<bool>,
 }
 
@@ -129,6 +137,35 @@ enum AgentSortField {
     Rating,
     RegisteredAt,
     SuccessRate,
+    WeightedScore,
+}
+
+impl AgentSortField {
+    /// Compare two agents by this field, in ascending order
+    fn compare(self, a: &AgentProfile, b: &AgentProfile) -> std::cmp::Ordering {
+        match self {
+            AgentSortField::JobsCompleted => a.jobs_completed.cmp(&b.jobs_completed),
+            AgentSortField::Rating => a
+                .average_rating
+                .partial_cmp(&b.average_rating)
+                .unwrap_or(std::cmp::Ordering::Equal),
+            AgentSortField::RegisteredAt => a.registered_at.cmp(&b.registered_at),
+            AgentSortField::SuccessRate => a.success_rate.cmp(&b.success_rate),
+            AgentSortField::WeightedScore => a
+                .weighted_score
+                .partial_cmp(&b.weighted_score)
+                .unwrap_or(std::cmp::Ordering::Equal),
+        }
+    }
+}
+
+/// Client filter options
+#[derive(InputObject, Default)]
+struct ClientFilter {
+    /// Minimum average rating (1-5)
+    min_rating: Option<f64>,
+    /// Minimum number of ratings received
+    min_ratings: Option<u64>,
 }
 
 /// Dispute filter options
@@ -145,26 +182,56 @@ struct DisputeFilter {
 /// GraphQL Query Root - Read state from the blockchain
 struct QueryRoot {
     state: Arc<JobMarketplace>,
+    runtime: Arc<ServiceRuntime<JobMarketplaceService>>,
 }
 
-#[Object]
 impl QueryRoot {
-    /// Get service status
-    async fn hello(&self) -> String {
-        "Job Marketplace v2.0 - Linera Blockchain with Escrow, Disputes & Messaging".to_string()
+    /// Number of jobs in a status (or all jobs), read from the maintained counters
+    async fn status_count(&self, status: Option<JobStatus>) -> async_graphql::Result<u64> {
+        match status {
+            Some(status) => Ok(self.state.status_counts().get(&status).await?.unwrap_or(0)),
+            None => {
+                let mut total = 0u64;
+                self.state
+                    .status_counts()
+                    .for_each_index_value(|_, count| {
+                        total += *count;
+                        Ok(())
+                    })
+                    .await?;
+                Ok(total)
+            }
+        }
     }
 
-    // ==================== JOB QUERIES ====================
+    /// Average of an amount over `count` items, rounded down (zero when there are none)
+    fn average_amount(total: Amount, count: u64) -> Amount {
+        total
+            .to_attos()
+            .checked_div(u128::from(count))
+            .map_or(Amount::ZERO, Amount::from_attos)
+    }
 
-    /// Get all jobs in the marketplace with optional filtering and sorting
-    async fn jobs(
+    /// Up to `limit` ratings received by an agent, newest first
+    async fn agent_reviews(&self, agent: AccountOwner, limit: usize) -> async_graphql::Result<Vec<AgentRating>> {
+        let rating_ids = self.state.agent_ratings().get(&agent).await?.unwrap_or_default();
+        // Ratings are indexed in submission order, so the newest are at the end
+        let mut ratings = Vec::new();
+        for id in rating_ids.iter().rev().take(limit) {
+            if let Some(rating) = self.state.ratings().get(id).await? {
+                ratings.push(rating);
+            }
+        }
+        Ok(ratings)
+    }
+
+    /// Every job matching the filter, sorted
+    async fn matching_jobs(
         &self,
         filter: Option<JobFilter>,
         sort_by: Option<JobSortField>,
         sort_dir: Option<SortDirection>,
-        limit: Option<usize>,
-        offset: Option<usize>,
-    ) -> Vec<Job> {
+    ) -> async_graphql::Result<Vec<Job>> {
         let mut jobs = Vec::new();
 
         let next_id = *self.state.next_job_id().get();
@@ -172,11 +239,13 @@ impl QueryRoot {
         // Start from 0 to handle both uninitialized state (first job ID = 0)
         // and initialized state (first job ID = 1)
         for id in 0..next_id.max(100) {
-            if let Ok(Some(job)) = self.state.jobs().get(&id).await {
-                jobs.push(job.clone());
+            if let Some(job) = self.state.jobs().get(&id).await? {
+                jobs.push(job);
             }
         }
 
+        let now = self.runtime.system_time();
+
         // Apply filters
         if let Some(f) = filter {
             jobs.retain(|job| {
@@ -194,13 +263,18 @@ impl QueryRoot {
                     }
                 }
 
-                // Tags filter (any match)
+                // Tags filter (any or all match)
                 if let Some(ref tags) = f.tags {
                     if !tags.is_empty() {
-                        let has_matching_tag = tags.iter().any(|t| {
+                        let tag_matches = |t: &String| {
                             job.tags.iter().any(|jt| jt.to_lowercase().contains(&t.to_lowercase()))
-                        });
-                        if !has_matching_tag {
+                        };
+                        let has_matching_tags = if f.match_all_tags == Some(true) {
+                            tags.iter().all(tag_matches)
+                        } else {
+                            tags.iter().any(tag_matches)
+                        };
+                        if !has_matching_tags {
                             return false;
                         }
                     }
@@ -208,9 +282,8 @@ impl QueryRoot {
 
                 // Min payment filter
                 if let Some(ref min) = f.min_payment {
-                    if let Ok(min_amount) = min.parse::<f64>() {
-                        let job_payment: f64 = job.payment.to_string().parse().unwrap_or(0.0);
-                        if job_payment < min_amount {
+                    if let Ok(min_amount) = min.parse::<Amount>() {
+                        if job.payment < min_amount {
                             return false;
                         }
                     }
@@ -218,9 +291,8 @@ impl QueryRoot {
 
                 // Max payment filter
                 if let Some(ref max) = f.max_payment {
-                    if let Ok(max_amount) = max.parse::<f64>() {
-                        let job_payment: f64 = job.payment.to_string().parse().unwrap_or(0.0);
-                        if job_payment > max_amount {
+                    if let Ok(max_amount) = max.parse::<Amount>() {
+                        if job.payment > max_amount {
                             return false;
                         }
                     }
@@ -246,6 +318,23 @@ impl QueryRoot {
                     }
                 }
 
+                // Creation time range
+                if f.created_after.is_some_and(|after| job.created_at < after) {
+                    return false;
+                }
+                if f.created_before.is_some_and(|before| job.created_at > before) {
+                    return false;
+                }
+
+                // Expired filter
+                if f.exclude_expired == Some(true) {
+                    let past_deadline = job.status == JobStatus::Posted
+                        && job.deadline.is_some_and(|deadline| now > deadline);
+                    if job.status == JobStatus::Expired || past_deadline {
+                        return false;
+                    }
+                }
+
                 true
             });
         }
@@ -269,101 +358,30 @@ impl QueryRoot {
             }
         });
 
-        // Apply pagination
-        let offset = offset.unwrap_or(0);
-        let limit = limit.unwrap_or(100);
-
-        jobs.into_iter().skip(offset).take(limit).collect()
-    }
-
-    /// Get a specific job by ID
-    async fn job(&self, id: u64) -> Option<Job> {
-        match self.state.jobs().get(&id).await {
-            Ok(Some(job)) => Some(job.clone()),
-            _ => None,
-        }
-    }
-
-    /// Get jobs by category
-    async fn jobs_by_category(&self, category: JobCategory) -> Vec<Job> {
-        let mut jobs = Vec::new();
-        let next_id = *self.state.next_job_id().get();
-
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(job)) = self.state.jobs().get(&id).await {
-                if job.category == category {
-                    jobs.push(job.clone());
-                }
-            }
-        }
-        jobs
-    }
-
-    /// Search jobs by keyword in title/description
-    async fn search_jobs(&self, query: String) -> Vec<Job> {
-        let mut jobs = Vec::new();
-        let query_lower = query.to_lowercase();
-        let next_id = *self.state.next_job_id().get();
-
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(job)) = self.state.jobs().get(&id).await {
-                if job.title.to_lowercase().contains(&query_lower)
-                    || job.description.to_lowercase().contains(&query_lower)
-                    || job.tags.iter().any(|t| t.to_lowercase().contains(&query_lower))
-                {
-                    jobs.push(job.clone());
-                }
-            }
-        }
-        jobs
-    }
-
-    /// Get jobs count by status
-    async fn jobs_count(&self, status: Option<JobStatus>) -> u64 {
-        let mut count = 0u64;
-        let next_id = *self.state.next_job_id().get();
-
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(job)) = self.state.jobs().get(&id).await {
-                if let Some(ref s) = status {
-                    if job.status == *s {
-                        count += 1;
-                    }
-                } else {
-                    count += 1;
-                }
-            }
-        }
-
-        count
+        Ok(jobs)
     }
 
-    // ==================== AGENT QUERIES ====================
-
-    /// Get all registered agents with optional filtering and sorting
-    async fn agents(
+    /// Every agent matching the filter, sorted
+    async fn matching_agents(
         &self,
         filter: Option<AgentFilter>,
         sort_by: Option<AgentSortField>,
         sort_dir: Option<SortDirection>,
-        limit: Option<usize>,
-        offset: Option<usize>,
-    ) -> Vec<AgentProfile> {
+    ) -> async_graphql::Result<Vec<AgentProfile>> {
         let mut owners = Vec::new();
 
-        let _ = self
-            .state
+        self.state
             .agents()
             .for_each_index(|owner| {
-                owners.push(owner.clone());
+                owners.push(owner);
                 Ok(())
             })
-            .await;
+            .await?;
 
         let mut profiles = Vec::new();
         for owner in owners {
-            if let Ok(Some(profile)) = self.state.agents().get(&owner).await {
-                profiles.push(profile.clone());
+            if let Some(profile) = self.state.agents().get(&owner).await? {
+                profiles.push(profile);
             }
         }
 
@@ -380,9 +398,7 @@ impl QueryRoot {
                 // Min rating filter
                 if let Some(min_rating) = f.min_rating {
                     if agent.total_ratings > 0 {
-                        let avg_rating =
-                            agent.total_rating_points as f64 / agent.total_ratings as f64;
-                        if avg_rating < min_rating {
+                        if agent.average_rating < min_rating {
                             return false;
                         }
                     } else if min_rating > 0.0 {
@@ -425,26 +441,7 @@ impl QueryRoot {
         let sort_direction = sort_dir.unwrap_or_default();
 
         profiles.sort_by(|a, b| {
-            let cmp = match sort_field {
-                AgentSortField::JobsCompleted => a.jobs_completed.cmp(&b.jobs_completed),
-                AgentSortField::Rating => {
-                    let rating_a = if a.total_ratings > 0 {
-                        a.total_rating_points as f64 / a.total_ratings as f64
-                    } else {
-                        0.0
-                    };
-                    let rating_b = if b.total_ratings > 0 {
-                        b.total_rating_points as f64 / b.total_ratings as f64
-                    } else {
-                        0.0
-                    };
-                    rating_a
-                        .partial_cmp(&rating_b)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                }
-                AgentSortField::RegisteredAt => a.registered_at.cmp(&b.registered_at),
-                AgentSortField::SuccessRate => a.success_rate.cmp(&b.success_rate),
-            };
+            let cmp = sort_field.compare(a, b);
 
             match sort_direction {
                 SortDirection::Asc => cmp,
@@ -452,77 +449,304 @@ impl QueryRoot {
             }
         });
 
-        // Apply pagination
+        Ok(profiles)
+    }
+}
+
+#[Object]
+impl QueryRoot {
+    /// Get service status
+    async fn hello(&self) -> String {
+        "Job Marketplace v2.0 - Linera Blockchain with Escrow, Disputes & Messaging".to_string()
+    }
+
+    // ==================== JOB QUERIES ====================
+
+    /// Get all jobs in the marketplace with optional filtering and sorting
+    async fn jobs(
+        &self,
+        filter: Option<JobFilter>,
+        sort_by: Option<JobSortField>,
+        sort_dir: Option<SortDirection>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Vec<Job>> {
+        let jobs = self.matching_jobs(filter, sort_by, sort_dir).await?;
+        Ok(jobs.into_iter().skip(offset.unwrap_or(0)).take(limit.unwrap_or(100)).collect())
+    }
+
+    /// Like `jobs`, but also returns the number of jobs matching the filter
+    async fn jobs_page(
+        &self,
+        filter: Option<JobFilter>,
+        sort_by: Option<JobSortField>,
+        sort_dir: Option<SortDirection>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<JobListPage> {
+        let jobs = self.matching_jobs(filter, sort_by, sort_dir).await?;
         let offset = offset.unwrap_or(0);
-        let limit = limit.unwrap_or(100);
+        Ok(JobListPage {
+            total: jobs.len() as u64,
+            offset,
+            items: jobs.into_iter().skip(offset).take(limit.unwrap_or(100)).collect(),
+        })
+    }
+
+    /// Page through jobs in ID order, starting after `cursor`.
+    /// Only reads the jobs on the requested page, so this is the scalable way to
+    /// list the marketplace; `jobs` loads and sorts everything on every call.
+    async fn jobs_after(&self, cursor: Option<u64>, limit: usize) -> async_graphql::Result<JobPage> {
+        let next_id = *self.state.next_job_id().get();
+        let mut items = Vec::new();
+        let mut id = cursor.map_or(0, |c| c.saturating_add(1));
 
-        profiles.into_iter().skip(offset).take(limit).collect()
+        while id < next_id && items.len() < limit {
+            if let Some(job) = self.state.jobs().get(&id).await? {
+                items.push(job);
+            }
+            id += 1;
+        }
+
+        // The cursor is the last ID examined, so gaps are never re-read
+        let next_cursor = if id < next_id {
+            Some(id.saturating_sub(1))
+        } else {
+            None
+        };
+
+        Ok(JobPage { items, next_cursor })
     }
 
-    /// Get a specific agent by owner address
-    async fn agent(&self, owner: String) -> Option<AgentProfile> {
+    /// Get a specific job by ID
+    async fn job(&self, id: u64) -> async_graphql::Result<Option<Job>> {
+        Ok(self.state.jobs().get(&id).await?)
+    }
+
+    /// Get aggregate statistics over a job's bids, or null if the job doesn't exist
+    async fn job_bid_summary(&self, job_id: u64) -> async_graphql::Result<Option<BidSummary>> {
+        let Some(job) = self.state.jobs().get(&job_id).await? else {
+            return Ok(None);
+        };
+
+        let count = job.bids.len() as u64;
+        let lowest = job.bids.iter().min_by_key(|b| (b.amount, b.timestamp));
+        let max_amount = job.bids.iter().map(|b| b.amount).max().unwrap_or(Amount::ZERO);
+        let total = job
+            .bids
+            .iter()
+            .try_fold(Amount::ZERO, |total, b| total.try_add(b.amount))
+            .map_err(|_| async_graphql::Error::new("Bid total overflows Amount"))?;
+        let avg_amount = Self::average_amount(total, count);
+
+        Ok(Some(BidSummary {
+            count,
+            min_amount: lowest.map_or(Amount::ZERO, |b| b.amount),
+            max_amount,
+            avg_amount,
+            lowest_bidder: lowest.map(|b| b.agent),
+        }))
+    }
+
+    /// Get jobs by category
+    async fn jobs_by_category(&self, category: JobCategory) -> async_graphql::Result<Vec<Job>> {
+        let mut jobs = Vec::new();
+        let next_id = *self.state.next_job_id().get();
+
+        for id in 1..next_id {
+            if let Some(job) = self.state.jobs().get(&id).await? {
+                if job.category == category {
+                    jobs.push(job);
+                }
+            }
+        }
+        Ok(jobs)
+    }
+
+    /// Search jobs by keyword in title, description or tags, newest first.
+    /// Matching is a case-insensitive substring test, so this scans every job
+    /// up to `next_job_id` (O(n) in the number of jobs ever posted).
+    async fn search_jobs(&self, query: String, limit: Option<usize>) -> async_graphql::Result<Vec<Job>> {
+        let mut jobs = Vec::new();
+        let query_lower = query.to_lowercase();
+        let next_id = *self.state.next_job_id().get();
+
+        for id in 0..next_id {
+            if let Some(job) = self.state.jobs().get(&id).await? {
+                if job.title.to_lowercase().contains(&query_lower)
+                    || job.description.to_lowercase().contains(&query_lower)
+                    || job.tags.iter().any(|t| t.to_lowercase().contains(&query_lower))
+                {
+                    jobs.push(job.clone());
+                }
+            }
+        }
+
+        jobs.sort_by_key(|j| std::cmp::Reverse(j.created_at));
+        jobs.truncate(limit.unwrap_or(100));
+        Ok(jobs)
+    }
+
+    /// Get jobs count by status
+    async fn jobs_count(&self, status: Option<JobStatus>) -> async_graphql::Result<u64> {
+        self.status_count(status).await
+    }
+
+    // ==================== AGENT QUERIES ====================
+
+    /// Get all registered agents with optional filtering and sorting
+    async fn agents(
+        &self,
+        filter: Option<AgentFilter>,
+        sort_by: Option<AgentSortField>,
+        sort_dir: Option<SortDirection>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Vec<AgentProfile>> {
+        let profiles = self.matching_agents(filter, sort_by, sort_dir).await?;
+        Ok(profiles.into_iter().skip(offset.unwrap_or(0)).take(limit.unwrap_or(100)).collect())
+    }
+
+    /// Like `agents`, but also returns the number of agents matching the filter
+    async fn agents_page(
+        &self,
+        filter: Option<AgentFilter>,
+        sort_by: Option<AgentSortField>,
+        sort_dir: Option<SortDirection>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<AgentPage> {
+        let profiles = self.matching_agents(filter, sort_by, sort_dir).await?;
+        let offset = offset.unwrap_or(0);
+        Ok(AgentPage {
+            total: profiles.len() as u64,
+            offset,
+            items: profiles.into_iter().skip(offset).take(limit.unwrap_or(100)).collect(),
+        })
+    }
+
+    /// Leaderboard of the best agents by a metric, highest first.
+    /// Rating-based metrics only rank agents with MIN_RATINGS_FOR_RANKING ratings.
+    async fn top_agents(&self, by: AgentSortField, limit: usize) -> async_graphql::Result<Vec<RankedAgent>> {
         let mut owners = Vec::new();
-        let _ = self
-            .state
+        self.state
             .agents()
-            .for_each_index(|o| {
-                owners.push(o.clone());
+            .for_each_index(|owner| {
+                owners.push(owner);
                 Ok(())
             })
-            .await;
+            .await?;
 
-        for o in owners {
-            if format!("{:?}", o).contains(&owner) || owner.contains(&format!("{:?}", o)) {
-                if let Ok(Some(profile)) = self.state.agents().get(&o).await {
-                    return Some(profile.clone());
+        let rating_based = matches!(by, AgentSortField::Rating | AgentSortField::WeightedScore);
+        let mut profiles = Vec::new();
+        for owner in owners {
+            if let Some(profile) = self.state.agents().get(&owner).await? {
+                if !rating_based || profile.total_ratings >= MIN_RATINGS_FOR_RANKING {
+                    profiles.push(profile);
                 }
             }
         }
-        None
+
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Only the top `limit` agents need to be fully sorted
+        let best_first = |a: &AgentProfile, b: &AgentProfile| by.compare(b, a);
+        if profiles.len() > limit {
+            profiles.select_nth_unstable_by(limit - 1, best_first);
+            profiles.truncate(limit);
+        }
+        profiles.sort_by(best_first);
+
+        Ok(profiles
+            .into_iter()
+            .enumerate()
+            .map(|(i, agent)| RankedAgent {
+                rank: i as u64 + 1,
+                agent,
+            })
+            .collect())
+    }
+
+    /// Get a specific agent by owner address
+    async fn agent(&self, owner: String) -> async_graphql::Result<Option<AgentProfile>> {
+        let Ok(owner) = owner.parse::<AccountOwner>() else {
+            return Ok(None);
+        };
+        Ok(self.state.agents().get(&owner).await?)
+    }
+
+    /// Check whether an agent has been banned by the admin
+    async fn is_banned(&self, owner: String) -> async_graphql::Result<bool> {
+        let owner = owner.parse::<AccountOwner>()?;
+        Ok(self.state.banned().contains_key(&owner).await?)
+    }
+
+    /// Get the jobs an agent has completed, newest first.
+    /// Only the most recent MAX_AGENT_JOB_HISTORY jobs are retained.
+    async fn agent_completed_jobs(
+        &self,
+        owner: String,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Vec<Job>> {
+        let owner = owner.parse::<AccountOwner>()?;
+        let Some(job_ids) = self.state.agent_jobs().get(&owner).await? else {
+            return Ok(Vec::new());
+        };
+
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(100);
+        let mut jobs = Vec::new();
+        for id in job_ids.iter().rev().skip(offset).take(limit) {
+            if let Some(job) = self.state.jobs().get(id).await? {
+                jobs.push(job);
+            }
+        }
+        Ok(jobs)
     }
 
     /// Get agents by skill
-    async fn agents_by_skill(&self, skill: String) -> Vec<AgentProfile> {
+    async fn agents_by_skill(&self, skill: String) -> async_graphql::Result<Vec<AgentProfile>> {
         let mut owners = Vec::new();
-        let _ = self
-            .state
+        self.state
             .agents()
             .for_each_index(|o| {
-                owners.push(o.clone());
+                owners.push(o);
                 Ok(())
             })
-            .await;
+            .await?;
 
         let mut profiles = Vec::new();
         let skill_lower = skill.to_lowercase();
 
         for owner in owners {
-            if let Ok(Some(profile)) = self.state.agents().get(&owner).await {
+            if let Some(profile) = self.state.agents().get(&owner).await? {
                 if profile.skills.iter().any(|s| s.to_lowercase().contains(&skill_lower)) {
-                    profiles.push(profile.clone());
+                    profiles.push(profile);
                 }
             }
         }
-        profiles
+        Ok(profiles)
     }
 
     /// Get verified agents only
-    async fn verified_agents(&self, min_level: Option<VerificationLevel>) -> Vec<AgentProfile> {
+    async fn verified_agents(&self, min_level: Option<VerificationLevel>) -> async_graphql::Result<Vec<AgentProfile>> {
         let mut owners = Vec::new();
-        let _ = self
-            .state
+        self.state
             .agents()
             .for_each_index(|o| {
-                owners.push(o.clone());
+                owners.push(o);
                 Ok(())
             })
-            .await;
+            .await?;
 
         let mut profiles = Vec::new();
         let min = min_level.unwrap_or(VerificationLevel::EmailVerified);
 
         for owner in owners {
-            if let Ok(Some(profile)) = self.state.agents().get(&owner).await {
+            if let Some(profile) = self.state.agents().get(&owner).await? {
                 // Check verification level is at least min_level
                 let level_val = match profile.verification_level {
                     VerificationLevel::Unverified => 0,
@@ -537,76 +761,208 @@ impl QueryRoot {
                     VerificationLevel::ProVerified => 3,
                 };
                 if level_val >= min_val {
-                    profiles.push(profile.clone());
+                    profiles.push(profile);
                 }
             }
         }
-        profiles
+        Ok(profiles)
     }
 
-    /// Get ratings for a specific agent
-    async fn agent_ratings(&self, _agent_owner: String) -> Vec<AgentRating> {
+    /// Get ratings for a specific agent, oldest first
+    async fn agent_ratings(&self, agent_owner: String) -> async_graphql::Result<Vec<AgentRating>> {
+        let agent = agent_owner.parse::<AccountOwner>()?;
+        let mut ratings = self.agent_reviews(agent, usize::MAX).await?;
+        ratings.reverse();
+        Ok(ratings)
+    }
+
+    /// Get an agent's profile with their most recent reviews, newest first
+    async fn agent_profile_with_reviews(
+        &self,
+        owner: String,
+        review_limit: Option<usize>,
+    ) -> async_graphql::Result<Option<AgentWithReviews>> {
+        let owner = owner.parse::<AccountOwner>()?;
+        let Some(profile) = self.state.agents().get(&owner).await? else {
+            return Ok(None);
+        };
+        let recent_reviews = self.agent_reviews(owner, review_limit.unwrap_or(10)).await?;
+        Ok(Some(AgentWithReviews { profile, recent_reviews }))
+    }
+
+    /// Get ratings written by a rater, newest first.
+    /// Scans every rating; acceptable since ratings are few relative to jobs.
+    async fn ratings_by_rater(&self, rater: String, limit: Option<usize>) -> async_graphql::Result<Vec<AgentRating>> {
         let mut ratings = Vec::new();
+        let rater = rater.parse::<AccountOwner>()?;
         let next_id = *self.state.next_rating_id().get();
 
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(rating)) = self.state.ratings().get(&id).await {
-                ratings.push(rating.clone());
+        for id in 0..next_id {
+            if let Some(rating) = self.state.ratings().get(&id).await? {
+                if rating.rater == rater {
+                    ratings.push(rating);
+                }
             }
         }
 
-        ratings
+        ratings.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+        ratings.truncate(limit.unwrap_or(100));
+        Ok(ratings)
     }
 
     /// Get total number of registered agents
-    async fn agents_count(&self) -> u64 {
+    async fn agents_count(&self) -> async_graphql::Result<u64> {
         let mut count = 0u64;
-        let _ = self
-            .state
+        self.state
             .agents()
             .for_each_index(|_| {
                 count += 1;
                 Ok(())
             })
-            .await;
-        count
+            .await?;
+        Ok(count)
+    }
+
+    // ==================== CLIENT QUERIES ====================
+
+    /// Get all rated clients with optional filtering, best rated first
+    async fn clients(
+        &self,
+        filter: Option<ClientFilter>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Vec<ClientProfile>> {
+        let mut owners = Vec::new();
+        self.state
+            .clients()
+            .for_each_index(|o| {
+                owners.push(o);
+                Ok(())
+            })
+            .await?;
+
+        let mut profiles = Vec::new();
+        for owner in owners {
+            if let Some(profile) = self.state.clients().get(&owner).await? {
+                profiles.push(profile);
+            }
+        }
+
+        // Apply filters
+        if let Some(f) = filter {
+            profiles.retain(|client| {
+                if let Some(min_rating) = f.min_rating {
+                    if client.average_rating < min_rating {
+                        return false;
+                    }
+                }
+                if let Some(min_ratings) = f.min_ratings {
+                    if client.total_ratings < min_ratings {
+                        return false;
+                    }
+                }
+                true
+            });
+        }
+
+        profiles.sort_by(|a, b| {
+            b.average_rating
+                .partial_cmp(&a.average_rating)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        // Apply pagination
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(100);
+
+        Ok(profiles.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Get a specific client's reputation by owner address
+    async fn client(&self, owner: String) -> async_graphql::Result<Option<ClientProfile>> {
+        let Ok(owner) = owner.parse::<AccountOwner>() else {
+            return Ok(None);
+        };
+        Ok(self.state.clients().get(&owner).await?)
+    }
+
+    /// Get the rating an agent left for the client of a job
+    async fn client_rating(&self, job_id: u64) -> async_graphql::Result<Option<ClientRating>> {
+        Ok(self.state.client_ratings().get(&job_id).await?)
+    }
+
+    /// Get the jobs a user has bookmarked, in the order they were added.
+    /// Jobs removed since bookmarking are skipped.
+    async fn my_favorites(&self, owner: String) -> async_graphql::Result<Vec<Job>> {
+        let owner = owner.parse::<AccountOwner>()?;
+        let job_ids = self.state.favorites().get(&owner).await?.unwrap_or_default();
+
+        let mut jobs = Vec::new();
+        for id in job_ids {
+            if let Some(job) = self.state.jobs().get(&id).await? {
+                jobs.push(job);
+            }
+        }
+        Ok(jobs)
+    }
+
+    // ==================== JOB BOARD QUERIES ====================
+
+    /// Get jobs announced by other chains, newest first
+    async fn remote_jobs(&self, limit: Option<usize>) -> async_graphql::Result<Vec<RemoteJob>> {
+        let mut keys = Vec::new();
+        self.state
+            .remote_jobs()
+            .for_each_index(|key| {
+                keys.push(key);
+                Ok(())
+            })
+            .await?;
+
+        let mut jobs = Vec::new();
+        for key in keys {
+            if let Some(job) = self.state.remote_jobs().get(&key).await? {
+                jobs.push(job);
+            }
+        }
+
+        jobs.sort_by_key(|j| std::cmp::Reverse(j.received_at));
+        jobs.truncate(limit.unwrap_or(100));
+        Ok(jobs)
     }
 
     // ==================== ESCROW QUERIES ====================
 
     /// Get escrow info for a job
-    async fn escrow(&self, job_id: u64) -> Option<EscrowInfo> {
-        match self.state.escrow().get(&job_id).await {
-            Ok(Some(escrow)) => Some(escrow.clone()),
-            _ => None,
-        }
+    async fn escrow(&self, job_id: u64) -> async_graphql::Result<Option<EscrowInfo>> {
+        Ok(self.state.escrow().get(&job_id).await?)
     }
 
     /// Get all active escrows
-    async fn active_escrows(&self) -> Vec<EscrowInfo> {
+    async fn active_escrows(&self) -> async_graphql::Result<Vec<EscrowInfo>> {
         let mut escrows = Vec::new();
         let next_id = *self.state.next_job_id().get();
 
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(escrow)) = self.state.escrow().get(&id).await {
+        for id in 1..next_id {
+            if let Some(escrow) = self.state.escrow().get(&id).await? {
                 if escrow.status == job_marketplace::EscrowStatus::Locked {
-                    escrows.push(escrow.clone());
+                    escrows.push(escrow);
                 }
             }
         }
-        escrows
+        Ok(escrows)
     }
 
     // ==================== DISPUTE QUERIES ====================
 
     /// Get all disputes with optional filtering
-    async fn disputes(&self, filter: Option<DisputeFilter>) -> Vec<Dispute> {
+    async fn disputes(&self, filter: Option<DisputeFilter>) -> async_graphql::Result<Vec<Dispute>> {
         let mut disputes = Vec::new();
         let next_id = *self.state.next_dispute_id().get();
 
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(dispute)) = self.state.disputes().get(&id).await {
-                disputes.push(dispute.clone());
+        for id in 1..next_id {
+            if let Some(dispute) = self.state.disputes().get(&id).await? {
+                disputes.push(dispute);
             }
         }
 
@@ -627,113 +983,99 @@ impl QueryRoot {
             });
         }
 
-        disputes
+        Ok(disputes)
     }
 
     /// Get a specific dispute by ID
-    async fn dispute(&self, id: u64) -> Option<Dispute> {
-        match self.state.disputes().get(&id).await {
-            Ok(Some(dispute)) => Some(dispute.clone()),
-            _ => None,
-        }
+    async fn dispute(&self, id: u64) -> async_graphql::Result<Option<Dispute>> {
+        Ok(self.state.disputes().get(&id).await?)
     }
 
     /// Get open disputes count
-    async fn open_disputes_count(&self) -> u64 {
+    async fn open_disputes_count(&self) -> async_graphql::Result<u64> {
         let mut count = 0u64;
         let next_id = *self.state.next_dispute_id().get();
 
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(dispute)) = self.state.disputes().get(&id).await {
+        for id in 1..next_id {
+            if let Some(dispute) = self.state.disputes().get(&id).await? {
                 if dispute.status == DisputeStatus::Open || dispute.status == DisputeStatus::UnderReview {
                     count += 1;
                 }
             }
         }
-        count
+        Ok(count)
     }
 
     // ==================== MESSAGE QUERIES ====================
 
     /// Get messages for a job
-    async fn messages(&self, job_id: u64) -> Vec<ChatMessage> {
+    async fn messages(&self, job_id: u64) -> async_graphql::Result<Vec<ChatMessage>> {
         let mut messages = Vec::new();
         let next_id = *self.state.next_message_id().get();
 
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(msg)) = self.state.messages().get(&id).await {
+        for id in 1..next_id {
+            if let Some(msg) = self.state.messages().get(&id).await? {
                 if msg.job_id == job_id {
-                    messages.push(msg.clone());
+                    messages.push(msg);
                 }
             }
         }
 
         // Sort by timestamp
-        messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-        messages
+        messages.sort_by_key(|m| m.timestamp);
+        Ok(messages)
     }
 
     /// Get unread message count for a user
-    async fn unread_messages_count(&self, user: String) -> u64 {
+    async fn unread_messages_count(&self, user: String) -> async_graphql::Result<u64> {
         let mut count = 0u64;
         let next_id = *self.state.next_message_id().get();
 
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(msg)) = self.state.messages().get(&id).await {
+        for id in 1..next_id {
+            if let Some(msg) = self.state.messages().get(&id).await? {
                 if !msg.read && format!("{:?}", msg.recipient).contains(&user) {
                     count += 1;
                 }
             }
         }
-        count
+        Ok(count)
     }
 
     // ==================== STATISTICS ====================
 
     /// Get marketplace statistics
-    async fn stats(&self) -> MarketplaceStats {
-        let next_job_id = *self.state.next_job_id().get();
-
-        let mut total_jobs = 0u64;
-        let mut posted_jobs = 0u64;
-        let mut in_progress_jobs = 0u64;
-        let mut completed_jobs = 0u64;
-        let mut disputed_jobs = 0u64;
-        let mut total_payment = 0.0f64;
-        let mut total_bids = 0u64;
-
-        for id in 1..next_job_id {
-            if let Ok(Some(job)) = self.state.jobs().get(&id).await {
-                total_jobs += 1;
-                total_bids += job.bids.len() as u64;
-                let payment: f64 = job.payment.to_string().parse().unwrap_or(0.0);
-                total_payment += payment;
-
-                match job.status {
-                    JobStatus::Posted => posted_jobs += 1,
-                    JobStatus::InProgress | JobStatus::PendingApproval => in_progress_jobs += 1,
-                    JobStatus::Completed => completed_jobs += 1,
-                    JobStatus::Disputed => disputed_jobs += 1,
-                    _ => {}
-                }
-            }
+    async fn stats(&self) -> async_graphql::Result<MarketplaceStats> {
+        let total_jobs = self.status_count(None).await?;
+        let posted_jobs = self.status_count(Some(JobStatus::Posted)).await?;
+        let in_progress_jobs = self.status_count(Some(JobStatus::AwaitingConfirmation)).await?
+            + self.status_count(Some(JobStatus::InProgress)).await?
+            + self.status_count(Some(JobStatus::PendingApproval)).await?;
+        let completed_jobs = self.status_count(Some(JobStatus::Completed)).await?;
+        let disputed_jobs = self.status_count(Some(JobStatus::Disputed)).await?;
+        if *self.state.payment_volume_overflowed().get() {
+            return Err(async_graphql::Error::new("Total payment volume overflows Amount"));
         }
+        let total_payment = *self.state.total_payment_volume().get();
+        let total_bids = *self.state.total_bids().get();
+
+        // The volume covers every job ever posted, including removed ones; IDs start at 1
+        let jobs_ever_posted = self.state.next_job_id().get().saturating_sub(1);
+        let average_job_payment = Self::average_amount(total_payment, jobs_ever_posted);
 
         let mut agents_count = 0u64;
         let mut verified_agents = 0u64;
         let mut owners = Vec::new();
-        let _ = self
-            .state
+        self.state
             .agents()
             .for_each_index(|o| {
-                owners.push(o.clone());
+                owners.push(o);
                 Ok(())
             })
-            .await;
+            .await?;
 
         for owner in owners {
             agents_count += 1;
-            if let Ok(Some(profile)) = self.state.agents().get(&owner).await {
+            if let Some(profile) = self.state.agents().get(&owner).await? {
                 if profile.verification_level != VerificationLevel::Unverified {
                     verified_agents += 1;
                 }
@@ -741,9 +1083,9 @@ impl QueryRoot {
         }
 
         let next_dispute_id = *self.state.next_dispute_id().get();
-        let open_disputes = next_dispute_id - 1; // Simplified
+        let open_disputes = next_dispute_id.saturating_sub(1); // Simplified
 
-        MarketplaceStats {
+        Ok(MarketplaceStats {
             total_jobs,
             posted_jobs,
             in_progress_jobs,
@@ -752,6 +1094,7 @@ impl QueryRoot {
             total_agents: agents_count,
             verified_agents,
             total_payment_volume: total_payment.to_string(),
+            average_job_payment,
             total_bids,
             open_disputes,
             avg_bids_per_job: if total_jobs > 0 {
@@ -759,28 +1102,80 @@ impl QueryRoot {
             } else {
                 0.0
             },
-        }
+        })
     }
 
     /// Get category statistics
-    async fn category_stats(&self) -> Vec<CategoryStats> {
+    async fn category_stats(&self) -> async_graphql::Result<Vec<CategoryStats>> {
         let mut category_counts: std::collections::HashMap<JobCategory, u64> =
             std::collections::HashMap::new();
         let next_id = *self.state.next_job_id().get();
 
-        for id in 0..next_id.max(100) {
-            if let Ok(Some(job)) = self.state.jobs().get(&id).await {
+        for id in 1..next_id {
+            if let Some(job) = self.state.jobs().get(&id).await? {
                 *category_counts.entry(job.category).or_insert(0) += 1;
             }
         }
 
-        category_counts
+        Ok(category_counts
             .into_iter()
             .map(|(category, count)| CategoryStats { category, count })
-            .collect()
+            .collect())
     }
 }
 
+/// A page of jobs for cursor-based paging
+#[derive(async_graphql::SimpleObject)]
+struct JobPage {
+    items: Vec<Job>,
+    /// Cursor to pass to the next call, or null once all jobs were returned
+    next_cursor: Option<u64>,
+}
+
+/// A page of jobs with the size of the full result set
+#[derive(async_graphql::SimpleObject)]
+struct JobListPage {
+    items: Vec<Job>,
+    /// Number of jobs matching the filter, across all pages
+    total: u64,
+    offset: usize,
+}
+
+/// A page of agents with the size of the full result set
+#[derive(async_graphql::SimpleObject)]
+struct AgentPage {
+    items: Vec<AgentProfile>,
+    /// Number of agents matching the filter, across all pages
+    total: u64,
+    offset: usize,
+}
+
+/// Aggregate view of the bids on a job
+#[derive(async_graphql::SimpleObject)]
+struct BidSummary {
+    count: u64,
+    min_amount: Amount,
+    max_amount: Amount,
+    avg_amount: Amount,
+    /// Agent with the lowest bid (earliest wins ties)
+    lowest_bidder: Option<AccountOwner>,
+}
+
+/// An agent's profile together with their latest reviews
+#[derive(async_graphql::SimpleObject)]
+struct AgentWithReviews {
+    profile: AgentProfile,
+    recent_reviews: Vec<AgentRating>,
+}
+
+/// An agent's position on a leaderboard
+#[derive(async_graphql::SimpleObject)]
+struct RankedAgent {
+    /// 1-based position
+    rank: u64,
+    agent: AgentProfile,
+}
+
 /// Marketplace statistics
 #[derive(async_graphql::SimpleObject)]
 struct MarketplaceStats {
@@ -792,6 +1187,8 @@ struct MarketplaceStats {
     total_agents: u64,
     verified_agents: u64,
     total_payment_volume: String,
+    /// Total payment volume divided by the number of jobs ever posted
+    average_job_payment: Amount,
     total_bids: u64,
     open_disputes: u64,
     avg_bids_per_job: f64,
@@ -803,3 +1200,7 @@ struct CategoryStats {
     category: JobCategory,
     count: u64,
 }
+
+#[cfg(test)]
+#[path = "unit_tests/service.rs"]
+mod tests;
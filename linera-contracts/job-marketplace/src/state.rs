@@ -1,5 +0,0 @@
-/*!
-State management for contract and service
-*/
-
-pub use job_marketplace::*;
@@ -0,0 +1,2223 @@
+/*!
+Unit tests for the job marketplace contract, run against the mock runtime
+*/
+
+use job_marketplace::{
+    AgentProfile, DisputeStatus, EscrowStatus, InstantiationArgument, Job, JobCategory, JobEvent,
+    JobMarketplaceAbi, JobMarketplaceError, JobStatus, MarketplaceParameters, Message,
+    MilestoneInput, Operation, OperationResponse, DISPUTE_WINDOW, JOB_EVENTS_STREAM,
+    MAX_DESCRIPTION_LENGTH, MAX_FAVORITES, MAX_NAME_LENGTH, MAX_PROPOSAL_LENGTH, MAX_TAGS_PER_JOB,
+    RATING_HALF_LIFE,
+};
+use linera_sdk::{
+    linera_base_types::{
+        AccountOwner, Amount, ApplicationId, ChainId, CryptoHash, StreamName, TimeDelta, Timestamp,
+    },
+    util::BlockingWait,
+    Contract, ContractRuntime,
+};
+
+use super::JobMarketplaceContract;
+
+/// System time at instantiation, in seconds
+const START_SECS: u64 = 1_700_000_000;
+
+/// Tokens every test account starts with
+const STARTING_BALANCE: u128 = 1_000;
+
+type OperationResult = Result<OperationResponse, JobMarketplaceError>;
+
+// ==================== FIXTURES ====================
+
+fn owner(name: &str) -> AccountOwner {
+    CryptoHash::test_hash(name).into()
+}
+
+fn admin() -> AccountOwner {
+    owner("admin")
+}
+
+fn client() -> AccountOwner {
+    owner("client")
+}
+
+fn agent() -> AccountOwner {
+    owner("agent")
+}
+
+fn other_agent() -> AccountOwner {
+    owner("other-agent")
+}
+
+fn fee_recipient() -> AccountOwner {
+    owner("fee-recipient")
+}
+
+fn tokens(amount: u128) -> Amount {
+    Amount::from_tokens(amount)
+}
+
+fn parameters() -> MarketplaceParameters {
+    MarketplaceParameters {
+        fee_basis_points: 0,
+        fee_recipient: fee_recipient(),
+        job_board_chain_id: None,
+        max_open_jobs_per_client: None,
+        min_bid_stake: Amount::ZERO,
+        max_active_jobs_per_agent: None,
+        reassign_after_secs: 7 * 24 * 60 * 60,
+        confirm_within_secs: 2 * 24 * 60 * 60,
+    }
+}
+
+fn create_contract() -> JobMarketplaceContract {
+    create_contract_with(parameters())
+}
+
+fn create_contract_with(parameters: MarketplaceParameters) -> JobMarketplaceContract {
+    create_contract_from(
+        parameters,
+        InstantiationArgument {
+            admin: admin(),
+            min_job_payment: Amount::ZERO,
+        },
+    )
+}
+
+/// Instantiate the marketplace with funded test accounts and an empty escrow account
+fn create_contract_from(
+    parameters: MarketplaceParameters,
+    argument: InstantiationArgument,
+) -> JobMarketplaceContract {
+    let application_id = ApplicationId::new(CryptoHash::test_hash("job-marketplace"))
+        .with_abi::<JobMarketplaceAbi>();
+    let funded = [admin(), client(), agent(), other_agent(), fee_recipient()]
+        .into_iter()
+        .map(|owner| (owner, tokens(STARTING_BALANCE)));
+    let runtime = ContractRuntime::new()
+        .with_application_parameters(parameters)
+        .with_application_id(application_id)
+        .with_chain_id(ChainId(CryptoHash::test_hash("chain")))
+        .with_system_time(Timestamp::from(START_SECS * 1_000_000))
+        .with_authenticated_signer(None)
+        .with_owner_balances(funded.chain([(application_id.into(), Amount::ZERO)]));
+
+    let mut contract = JobMarketplaceContract::load(runtime).blocking_wait();
+    contract.instantiate(argument).blocking_wait();
+    contract
+}
+
+/// Execute an operation signed by `signer`
+fn execute(
+    contract: &mut JobMarketplaceContract,
+    signer: AccountOwner,
+    operation: Operation,
+) -> OperationResult {
+    contract.runtime.set_authenticated_signer(signer);
+    contract.execute_operation(operation).blocking_wait()
+}
+
+fn advance_secs(contract: &mut JobMarketplaceContract, secs: u64) {
+    let now = contract.runtime.system_time();
+    contract
+        .runtime
+        .set_system_time(now.saturating_add(TimeDelta::from_secs(secs)));
+}
+
+fn balance(contract: &mut JobMarketplaceContract, owner: AccountOwner) -> Amount {
+    contract.runtime.owner_balance(owner)
+}
+
+fn escrow_account_balance(contract: &mut JobMarketplaceContract) -> Amount {
+    let application = contract.runtime.application_id().into();
+    contract.runtime.owner_balance(application)
+}
+
+fn escrow_held(contract: &JobMarketplaceContract, job_id: u64) -> Amount {
+    contract
+        .state
+        .escrow_balance()
+        .get(&job_id)
+        .blocking_wait()
+        .expect("Failed to read escrow balance")
+        .unwrap_or(Amount::ZERO)
+}
+
+fn job(contract: &JobMarketplaceContract, job_id: u64) -> Job {
+    contract
+        .state
+        .jobs()
+        .get(&job_id)
+        .blocking_wait()
+        .expect("Failed to read job")
+        .expect("Job not found")
+}
+
+fn agent_profile(contract: &JobMarketplaceContract, agent: AccountOwner) -> AgentProfile {
+    contract
+        .state
+        .agents()
+        .get(&agent)
+        .blocking_wait()
+        .expect("Failed to read agent")
+        .expect("Agent not found")
+}
+
+fn agent_jobs(contract: &JobMarketplaceContract, agent: AccountOwner) -> Vec<u64> {
+    contract
+        .state
+        .agent_jobs()
+        .get(&agent)
+        .blocking_wait()
+        .expect("Failed to read agent jobs")
+        .unwrap_or_default()
+}
+
+fn open_bids(contract: &JobMarketplaceContract, agent: AccountOwner) -> Vec<u64> {
+    contract
+        .state
+        .open_bids()
+        .get(&agent)
+        .blocking_wait()
+        .expect("Failed to read open bids")
+        .unwrap_or_default()
+}
+
+fn post_job_operation(payment: Amount) -> Operation {
+    Operation::PostJob {
+        title: "Train a model".to_string(),
+        description: "Fine-tune a classifier".to_string(),
+        payment,
+        category: JobCategory::AIModel,
+        tags: vec![],
+        deadline: None,
+        milestones: vec![],
+        auto_accept_at: None,
+    }
+}
+
+/// Post a job as the client and return its ID
+fn post_job(contract: &mut JobMarketplaceContract, payment: Amount) -> u64 {
+    post_job_with(contract, post_job_operation(payment))
+}
+
+/// Execute a `PostJob` operation as the client and return the new job's ID
+fn post_job_with(contract: &mut JobMarketplaceContract, operation: Operation) -> u64 {
+    match execute(contract, client(), operation) {
+        Ok(OperationResponse::JobPosted { job_id }) => job_id,
+        other => panic!("Unexpected PostJob response: {other:?}"),
+    }
+}
+
+fn register_agent(contract: &mut JobMarketplaceContract, agent: AccountOwner) {
+    execute(
+        contract,
+        agent,
+        Operation::RegisterAgent {
+            name: "Agent".to_string(),
+            service_description: "Builds models".to_string(),
+            skills: vec!["rust".to_string()],
+            hourly_rate: None,
+        },
+    )
+    .expect("Failed to register agent");
+}
+
+fn bid_operation(job_id: u64, amount: Amount, stake: Amount) -> Operation {
+    Operation::PlaceBid {
+        job_id,
+        amount,
+        proposal: "I have done this before".to_string(),
+        estimated_days: 3,
+        stake,
+    }
+}
+
+/// Bid without a stake and return the new bid's ID
+fn place_bid(
+    contract: &mut JobMarketplaceContract,
+    agent: AccountOwner,
+    job_id: u64,
+    amount: Amount,
+) -> u64 {
+    match execute(contract, agent, bid_operation(job_id, amount, Amount::ZERO)) {
+        Ok(OperationResponse::BidPlaced { bid_id }) => bid_id,
+        other => panic!("Unexpected PlaceBid response: {other:?}"),
+    }
+}
+
+fn accept_bid(
+    contract: &mut JobMarketplaceContract,
+    job_id: u64,
+    agent: AccountOwner,
+    bid_amount: Amount,
+) -> OperationResult {
+    execute(
+        contract,
+        client(),
+        Operation::AcceptBid {
+            job_id,
+            agent,
+            bid_amount,
+        },
+    )
+}
+
+/// Bid the full payment and have the client accept and the agent confirm
+fn start_job(contract: &mut JobMarketplaceContract, job_id: u64, agent: AccountOwner) {
+    start_job_with_stake(contract, job_id, agent, Amount::ZERO);
+}
+
+fn start_job_with_stake(
+    contract: &mut JobMarketplaceContract,
+    job_id: u64,
+    agent: AccountOwner,
+    stake: Amount,
+) {
+    let payment = job(contract, job_id).payment;
+    execute(contract, agent, bid_operation(job_id, payment, stake)).expect("Failed to place bid");
+    accept_bid(contract, job_id, agent, payment).expect("Failed to accept bid");
+    execute(contract, agent, Operation::ConfirmJob { job_id }).expect("Failed to confirm job");
+}
+
+fn milestone_job_operation(payment: Amount, percentages: &[u8]) -> Operation {
+    let milestones: Vec<MilestoneInput> = percentages
+        .iter()
+        .map(|&payment_percentage| MilestoneInput {
+            title: "Milestone".to_string(),
+            description: "Part of the work".to_string(),
+            payment_percentage,
+            due_days: None,
+        })
+        .collect();
+    let mut operation = post_job_operation(payment);
+    if let Operation::PostJob {
+        milestones: slot, ..
+    } = &mut operation
+    {
+        *slot = milestones;
+    }
+    operation
+}
+
+// ==================== INSTANTIATION ====================
+
+#[test]
+fn instantiation_sets_the_admin_and_minimum_payment() {
+    let moderator = owner("moderator");
+    let mut contract = create_contract_from(
+        parameters(),
+        InstantiationArgument {
+            admin: moderator,
+            min_job_payment: tokens(50),
+        },
+    );
+    assert_eq!(*contract.state.admin().get(), Some(moderator));
+
+    let result = execute(&mut contract, client(), post_job_operation(tokens(49)));
+    assert!(matches!(result, Err(JobMarketplaceError::PaymentTooLow)));
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+    let job_id = post_job(&mut contract, tokens(50));
+    assert_eq!(job_id, 1);
+
+    // Only the configured admin can moderate
+    register_agent(&mut contract, agent());
+    assert!(matches!(
+        execute(
+            &mut contract,
+            admin(),
+            Operation::BanAgent { agent: agent() }
+        ),
+        Err(JobMarketplaceError::NotAuthorized)
+    ));
+    execute(
+        &mut contract,
+        moderator,
+        Operation::BanAgent { agent: agent() },
+    )
+    .unwrap();
+}
+
+// ==================== JOB POSTING ====================
+
+#[test]
+fn operations_return_the_ids_they_create() {
+    let mut contract = create_contract();
+
+    let first = post_job(&mut contract, tokens(100));
+    let second = post_job(&mut contract, tokens(50));
+    assert_eq!((first, second), (1, 2));
+    assert_eq!(job(&contract, second).payment, tokens(50));
+
+    register_agent(&mut contract, agent());
+    register_agent(&mut contract, other_agent());
+    assert_eq!(place_bid(&mut contract, agent(), first, tokens(90)), 0);
+    assert_eq!(
+        place_bid(&mut contract, other_agent(), first, tokens(90)),
+        1
+    );
+
+    assert!(matches!(
+        execute(
+            &mut contract,
+            agent(),
+            Operation::WithdrawBid { job_id: first }
+        ),
+        Ok(OperationResponse::Ok)
+    ));
+    let rebid = place_bid(&mut contract, agent(), first, tokens(80));
+
+    // Bid IDs are not reused while other bids remain
+    assert_eq!(rebid, 2);
+    let stored = job(&contract, first).bids;
+    let bid = stored.iter().find(|b| b.agent == agent()).unwrap();
+    assert_eq!((bid.bid_id, bid.amount), (2, tokens(80)));
+}
+
+#[test]
+fn jobs_are_limited_in_tags() {
+    let mut contract = create_contract();
+    let tagged_job = |count: usize| {
+        let mut operation = post_job_operation(tokens(100));
+        if let Operation::PostJob { tags, .. } = &mut operation {
+            *tags = (0..count).map(|i| format!("tag-{i}")).collect();
+        }
+        operation
+    };
+
+    let result = execute(&mut contract, client(), tagged_job(MAX_TAGS_PER_JOB + 1));
+    assert!(matches!(result, Err(JobMarketplaceError::TooManyTags)));
+
+    let job_id = post_job_with(&mut contract, tagged_job(MAX_TAGS_PER_JOB));
+    assert_eq!(job(&contract, job_id).tags.len(), MAX_TAGS_PER_JOB);
+}
+
+#[test]
+fn job_text_must_be_present_and_bounded() {
+    let mut contract = create_contract();
+    let job_text = |title: String, description: String| {
+        let mut operation = post_job_operation(tokens(100));
+        if let Operation::PostJob {
+            title: title_slot,
+            description: description_slot,
+            ..
+        } = &mut operation
+        {
+            *title_slot = title;
+            *description_slot = description;
+        }
+        operation
+    };
+
+    for (title, description, expected_too_long) in [
+        ("  ".to_string(), "Details".to_string(), false),
+        ("Title".to_string(), String::new(), false),
+        ("x".repeat(MAX_NAME_LENGTH + 1), "Details".to_string(), true),
+        (
+            "Title".to_string(),
+            "x".repeat(MAX_DESCRIPTION_LENGTH + 1),
+            true,
+        ),
+    ] {
+        let result = execute(&mut contract, client(), job_text(title, description));
+        if expected_too_long {
+            assert!(matches!(result, Err(JobMarketplaceError::InputTooLong)));
+        } else {
+            assert!(matches!(result, Err(JobMarketplaceError::InvalidInput)));
+        }
+    }
+    assert_eq!(*contract.state.next_job_id().get(), 1);
+
+    post_job_with(
+        &mut contract,
+        job_text(
+            "x".repeat(MAX_NAME_LENGTH),
+            "x".repeat(MAX_DESCRIPTION_LENGTH),
+        ),
+    );
+}
+
+#[test]
+fn clients_are_limited_in_open_jobs() {
+    let mut contract = create_contract_with(MarketplaceParameters {
+        max_open_jobs_per_client: Some(2),
+        ..parameters()
+    });
+    let first = post_job(&mut contract, tokens(100));
+    post_job(&mut contract, tokens(100));
+
+    let result = execute(&mut contract, client(), post_job_operation(tokens(100)));
+    assert!(matches!(result, Err(JobMarketplaceError::TooManyOpenJobs)));
+
+    execute(
+        &mut contract,
+        client(),
+        Operation::CancelJob { job_id: first },
+    )
+    .unwrap();
+    post_job(&mut contract, tokens(100));
+
+    // Other clients have their own allowance
+    execute(&mut contract, admin(), post_job_operation(tokens(100))).unwrap();
+}
+
+// ==================== ESCROW ====================
+
+#[test]
+fn post_job_locks_payment_in_escrow() {
+    let mut contract = create_contract();
+
+    let job_id = post_job(&mut contract, tokens(100));
+
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 100)
+    );
+    assert_eq!(escrow_account_balance(&mut contract), tokens(100));
+    assert_eq!(escrow_held(&contract, job_id), tokens(100));
+    assert_eq!(job(&contract, job_id).status, JobStatus::Posted);
+}
+
+#[test]
+fn post_job_without_funds_is_rejected() {
+    let mut contract = create_contract();
+
+    let result = execute(
+        &mut contract,
+        client(),
+        post_job_operation(tokens(STARTING_BALANCE + 1)),
+    );
+
+    assert!(matches!(
+        result,
+        Err(JobMarketplaceError::InsufficientFunds)
+    ));
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+    assert_eq!(*contract.state.next_job_id().get(), 1);
+}
+
+#[test]
+fn escrow_shortfall_leaves_the_job_untouched() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(60), tokens(10)),
+    )
+    .unwrap();
+    // Less is recorded as held than the 40 token surplus to refund on acceptance
+    contract
+        .state
+        .escrow_balance_mut()
+        .insert(&job_id, tokens(30))
+        .unwrap();
+
+    let result = accept_bid(&mut contract, job_id, agent(), tokens(60));
+
+    assert!(matches!(
+        result,
+        Err(JobMarketplaceError::InsufficientFunds)
+    ));
+    let job = job(&contract, job_id);
+    assert_eq!(job.status, JobStatus::Posted);
+    assert_eq!(job.agent, None);
+    assert_eq!(job.bids[0].stake, tokens(10));
+    assert_eq!(escrow_held(&contract, job_id), tokens(30));
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 100)
+    );
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE - 10)
+    );
+}
+
+#[test]
+fn payout_without_escrow_leaves_the_job_untouched() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job_with_stake(&mut contract, job_id, agent(), tokens(10));
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    advance_secs(&mut contract, DISPUTE_WINDOW.as_micros() / 1_000_000 + 1);
+    contract.state.escrow_balance_mut().remove(&job_id).unwrap();
+
+    let result = execute(&mut contract, agent(), Operation::ReleasePayment { job_id });
+
+    assert!(matches!(result, Err(JobMarketplaceError::EscrowNotFound)));
+    let escrow = contract
+        .state
+        .escrow()
+        .get(&job_id)
+        .blocking_wait()
+        .unwrap()
+        .unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Locked);
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE - 10)
+    );
+}
+
+#[test]
+fn completed_job_pays_the_agent_once_the_dispute_window_closes() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    assert_eq!(agent_profile(&contract, agent()).jobs_completed, 1);
+    assert!(matches!(
+        execute(&mut contract, agent(), Operation::ReleasePayment { job_id }),
+        Err(JobMarketplaceError::DisputeWindowOpen)
+    ));
+    assert_eq!(balance(&mut contract, agent()), tokens(STARTING_BALANCE));
+
+    advance_secs(&mut contract, DISPUTE_WINDOW.as_micros() / 1_000_000 + 1);
+    execute(&mut contract, agent(), Operation::ReleasePayment { job_id }).unwrap();
+
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE + 100)
+    );
+    assert_eq!(escrow_held(&contract, job_id), Amount::ZERO);
+    assert_eq!(escrow_account_balance(&mut contract), Amount::ZERO);
+}
+
+#[test]
+fn accepting_a_lower_bid_refunds_the_difference() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(60), Amount::ZERO),
+    )
+    .unwrap();
+
+    accept_bid(&mut contract, job_id, agent(), tokens(60)).unwrap();
+
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 60)
+    );
+    assert_eq!(escrow_held(&contract, job_id), tokens(60));
+    assert_eq!(job(&contract, job_id).accepted_bid_amount, Some(tokens(60)));
+
+    execute(&mut contract, agent(), Operation::ConfirmJob { job_id }).unwrap();
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    advance_secs(&mut contract, DISPUTE_WINDOW.as_micros() / 1_000_000 + 1);
+    execute(&mut contract, agent(), Operation::ReleasePayment { job_id }).unwrap();
+
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE + 60)
+    );
+}
+
+/// Change the payment of a job as the client
+fn edit_payment(
+    contract: &mut JobMarketplaceContract,
+    job_id: u64,
+    payment: Amount,
+) -> OperationResult {
+    execute(
+        contract,
+        client(),
+        Operation::EditJob {
+            job_id,
+            description: None,
+            payment: Some(payment),
+        },
+    )
+}
+
+#[test]
+fn editing_the_payment_adjusts_the_escrow() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+
+    edit_payment(&mut contract, job_id, tokens(150)).unwrap();
+    assert_eq!(escrow_held(&contract, job_id), tokens(150));
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 150)
+    );
+
+    edit_payment(&mut contract, job_id, tokens(80)).unwrap();
+    assert_eq!(escrow_held(&contract, job_id), tokens(80));
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 80)
+    );
+    assert_eq!(job(&contract, job_id).payment, tokens(80));
+    assert_eq!(escrow_account_balance(&mut contract), tokens(80));
+}
+
+#[test]
+fn jobs_cannot_be_edited_once_a_bid_is_accepted() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+
+    let result = edit_payment(&mut contract, job_id, tokens(50));
+
+    assert!(matches!(result, Err(JobMarketplaceError::InvalidStatus)));
+    assert_eq!(escrow_held(&contract, job_id), tokens(100));
+}
+
+#[test]
+fn settling_a_job_empties_its_escrow_and_closes_ratings() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job_with_stake(&mut contract, job_id, agent(), tokens(10));
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+
+    let early = execute(&mut contract, client(), Operation::SettleJob { job_id });
+    assert!(matches!(early, Err(JobMarketplaceError::InvalidStatus)));
+
+    advance_secs(&mut contract, DISPUTE_WINDOW.as_micros() / 1_000_000 + 1);
+    execute(&mut contract, client(), Operation::SettleJob { job_id }).unwrap();
+
+    // The unreleased payment and the stake go to the agent
+    assert!(job(&contract, job_id).settled);
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE + 100)
+    );
+    assert_eq!(escrow_held(&contract, job_id), Amount::ZERO);
+    assert_eq!(escrow_account_balance(&mut contract), Amount::ZERO);
+    assert!(matches!(
+        rate_agent(&mut contract, job_id, 5),
+        Err(JobMarketplaceError::InvalidStatus)
+    ));
+    assert!(matches!(
+        execute(&mut contract, client(), Operation::SettleJob { job_id }),
+        Err(JobMarketplaceError::InvalidStatus)
+    ));
+}
+
+#[test]
+fn settling_after_release_returns_nothing_further() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    advance_secs(&mut contract, DISPUTE_WINDOW.as_micros() / 1_000_000 + 1);
+    execute(&mut contract, agent(), Operation::ReleasePayment { job_id }).unwrap();
+
+    execute(&mut contract, client(), Operation::SettleJob { job_id }).unwrap();
+
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE + 100)
+    );
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 100)
+    );
+}
+
+#[test]
+fn cancel_job_refunds_escrow() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+
+    execute(&mut contract, client(), Operation::CancelJob { job_id }).unwrap();
+
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+    assert_eq!(escrow_held(&contract, job_id), Amount::ZERO);
+    assert_eq!(job(&contract, job_id).status, JobStatus::Cancelled);
+}
+
+// ==================== FEES ====================
+
+#[test]
+fn platform_fee_and_agent_share_add_up_to_the_payment() {
+    let mut contract = create_contract_with(MarketplaceParameters {
+        fee_basis_points: 250,
+        ..parameters()
+    });
+    // Not a multiple of 10 000 attos, so the fee gets rounded down
+    let payment = Amount::from_attos(100_000_000_000_000_000_007);
+    let job_id = post_job(&mut contract, payment);
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    advance_secs(&mut contract, DISPUTE_WINDOW.as_micros() / 1_000_000 + 1);
+
+    execute(&mut contract, agent(), Operation::ReleasePayment { job_id }).unwrap();
+
+    let fee = balance(&mut contract, fee_recipient()).saturating_sub(tokens(STARTING_BALANCE));
+    let agent_share = balance(&mut contract, agent()).saturating_sub(tokens(STARTING_BALANCE));
+    assert_eq!(fee, Amount::from_attos(2_500_000_000_000_000_000));
+    assert_eq!(fee.saturating_add(agent_share), payment);
+    assert_eq!(escrow_account_balance(&mut contract), Amount::ZERO);
+}
+
+#[test]
+#[should_panic(expected = "Fee basis points must not exceed 10000")]
+fn fees_above_one_hundred_percent_are_rejected() {
+    create_contract_with(MarketplaceParameters {
+        fee_basis_points: 10_001,
+        ..parameters()
+    });
+}
+
+// ==================== FAVORITES ====================
+
+fn favorites(contract: &JobMarketplaceContract, user: AccountOwner) -> Vec<u64> {
+    contract
+        .state
+        .favorites()
+        .get(&user)
+        .blocking_wait()
+        .expect("Failed to read favorites")
+        .unwrap_or_default()
+}
+
+#[test]
+fn favorites_are_added_and_removed() {
+    let mut contract = create_contract();
+    let first = post_job(&mut contract, tokens(100));
+    let second = post_job(&mut contract, tokens(100));
+
+    for job_id in [first, second, first] {
+        execute(&mut contract, agent(), Operation::AddFavorite { job_id }).unwrap();
+    }
+    assert_eq!(favorites(&contract, agent()), vec![first, second]);
+
+    let missing = execute(
+        &mut contract,
+        agent(),
+        Operation::AddFavorite { job_id: 99 },
+    );
+    assert!(matches!(missing, Err(JobMarketplaceError::JobNotFound(99))));
+
+    execute(
+        &mut contract,
+        agent(),
+        Operation::RemoveFavorite { job_id: first },
+    )
+    .unwrap();
+    assert_eq!(favorites(&contract, agent()), vec![second]);
+    assert!(favorites(&contract, other_agent()).is_empty());
+}
+
+#[test]
+fn favorites_are_capped() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    let full: Vec<u64> = (1_000..).take(MAX_FAVORITES).collect();
+    contract
+        .state
+        .favorites_mut()
+        .insert(&agent(), full.clone())
+        .unwrap();
+
+    let result = execute(&mut contract, agent(), Operation::AddFavorite { job_id });
+
+    assert!(matches!(result, Err(JobMarketplaceError::TooManyFavorites)));
+    assert_eq!(favorites(&contract, agent()), full);
+}
+
+// ==================== BIDDING ====================
+
+#[test]
+fn finalizing_an_auction_awards_the_lowest_earliest_bid() {
+    let mut contract = create_contract();
+    let mut operation = post_job_operation(tokens(100));
+    if let Operation::PostJob { auto_accept_at, .. } = &mut operation {
+        *auto_accept_at = Some(START_SECS + 60);
+    }
+    let job_id = post_job_with(&mut contract, operation);
+    let third_agent = owner("third-agent");
+    for (bidder, amount) in [(agent(), 80), (other_agent(), 60), (third_agent, 60)] {
+        register_agent(&mut contract, bidder);
+        execute(
+            &mut contract,
+            bidder,
+            bid_operation(job_id, tokens(amount), Amount::ZERO),
+        )
+        .unwrap();
+        advance_secs(&mut contract, 1);
+    }
+
+    let early = execute(
+        &mut contract,
+        agent(),
+        Operation::FinalizeAuction { job_id },
+    );
+    assert!(matches!(
+        early,
+        Err(JobMarketplaceError::DeadlineNotReached)
+    ));
+
+    advance_secs(&mut contract, 60);
+    execute(
+        &mut contract,
+        agent(),
+        Operation::FinalizeAuction { job_id },
+    )
+    .unwrap();
+
+    let job = job(&contract, job_id);
+    assert_eq!(job.status, JobStatus::AwaitingConfirmation);
+    assert_eq!(job.agent, Some(other_agent()));
+    assert_eq!(job.accepted_bid_amount, Some(tokens(60)));
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 60)
+    );
+    assert_eq!(escrow_held(&contract, job_id), tokens(60));
+}
+
+#[test]
+fn agents_at_capacity_cannot_be_awarded_more_jobs() {
+    let mut contract = create_contract_with(MarketplaceParameters {
+        max_active_jobs_per_agent: Some(2),
+        ..parameters()
+    });
+    register_agent(&mut contract, agent());
+    let first = post_job(&mut contract, tokens(100));
+    let second = post_job(&mut contract, tokens(100));
+    let third = post_job(&mut contract, tokens(100));
+    start_job(&mut contract, first, agent());
+    start_job(&mut contract, second, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(third, tokens(100), Amount::ZERO),
+    )
+    .unwrap();
+
+    let result = accept_bid(&mut contract, third, agent(), tokens(100));
+    assert!(matches!(result, Err(JobMarketplaceError::AgentAtCapacity)));
+    assert_eq!(job(&contract, third).status, JobStatus::Posted);
+
+    // Finishing a job frees up a slot
+    execute(
+        &mut contract,
+        client(),
+        Operation::CompleteJob { job_id: first },
+    )
+    .unwrap();
+    accept_bid(&mut contract, third, agent(), tokens(100)).unwrap();
+    assert_eq!(agent_profile(&contract, agent()).active_jobs, 2);
+}
+
+#[test]
+fn agents_cannot_bid_twice_on_a_job() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(90), tokens(10)),
+    )
+    .unwrap();
+
+    let result = execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(80), tokens(10)),
+    );
+
+    assert!(matches!(result, Err(JobMarketplaceError::AlreadyBid)));
+    let bids = job(&contract, job_id).bids;
+    assert_eq!(bids.len(), 1);
+    assert_eq!(bids[0].amount, tokens(90));
+    assert_eq!(*contract.state.total_bids().get(), 1);
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE - 10)
+    );
+}
+
+#[test]
+fn bid_proposals_are_bounded_in_length() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    let bid_with_proposal = |proposal: String| Operation::PlaceBid {
+        job_id,
+        amount: tokens(90),
+        proposal,
+        estimated_days: 3,
+        stake: Amount::ZERO,
+    };
+
+    let too_long = "x".repeat(MAX_PROPOSAL_LENGTH + 1);
+    let result = execute(&mut contract, agent(), bid_with_proposal(too_long));
+    assert!(matches!(result, Err(JobMarketplaceError::ProposalTooLong)));
+    assert!(job(&contract, job_id).bids.is_empty());
+
+    // The limit counts characters, not bytes
+    let longest = "é".repeat(MAX_PROPOSAL_LENGTH);
+    execute(&mut contract, agent(), bid_with_proposal(longest.clone())).unwrap();
+    assert_eq!(job(&contract, job_id).bids[0].proposal, longest);
+}
+
+#[test]
+fn withdrawing_a_bid_refunds_its_stake() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    register_agent(&mut contract, other_agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(90), tokens(10)),
+    )
+    .unwrap();
+
+    let stranger = execute(
+        &mut contract,
+        other_agent(),
+        Operation::WithdrawBid { job_id },
+    );
+    assert!(matches!(stranger, Err(JobMarketplaceError::NotAuthorized)));
+
+    execute(&mut contract, agent(), Operation::WithdrawBid { job_id }).unwrap();
+
+    assert!(job(&contract, job_id).bids.is_empty());
+    assert_eq!(*contract.state.total_bids().get(), 0);
+    assert_eq!(bid_stake(&contract, job_id, agent()), None);
+    assert_eq!(balance(&mut contract, agent()), tokens(STARTING_BALANCE));
+}
+
+#[test]
+fn accepted_bids_cannot_be_withdrawn() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+
+    let result = execute(&mut contract, agent(), Operation::WithdrawBid { job_id });
+
+    assert!(matches!(result, Err(JobMarketplaceError::InvalidStatus)));
+    assert_eq!(job(&contract, job_id).bids.len(), 1);
+}
+
+// ==================== CONFIRMATION ====================
+
+#[test]
+fn accepted_agents_confirm_before_work_starts() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    place_bid(&mut contract, agent(), job_id, tokens(100));
+
+    accept_bid(&mut contract, job_id, agent(), tokens(100)).unwrap();
+    assert_eq!(
+        job(&contract, job_id).status,
+        JobStatus::AwaitingConfirmation
+    );
+
+    let by_other = execute(
+        &mut contract,
+        other_agent(),
+        Operation::ConfirmJob { job_id },
+    );
+    assert!(matches!(by_other, Err(JobMarketplaceError::NotAuthorized)));
+
+    execute(&mut contract, agent(), Operation::ConfirmJob { job_id }).unwrap();
+    assert_eq!(job(&contract, job_id).status, JobStatus::InProgress);
+
+    let again = execute(&mut contract, agent(), Operation::ConfirmJob { job_id });
+    assert!(matches!(again, Err(JobMarketplaceError::InvalidStatus)));
+}
+
+#[test]
+fn unconfirmed_jobs_reopen_after_the_confirmation_window() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    register_agent(&mut contract, other_agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(60), tokens(10)),
+    )
+    .unwrap();
+    place_bid(&mut contract, other_agent(), job_id, tokens(80));
+    accept_bid(&mut contract, job_id, agent(), tokens(60)).unwrap();
+
+    let confirm_within_secs = parameters().confirm_within_secs;
+    advance_secs(&mut contract, confirm_within_secs);
+    let early = execute(&mut contract, client(), Operation::ReopenJob { job_id });
+    assert!(matches!(
+        early,
+        Err(JobMarketplaceError::ConfirmationPending)
+    ));
+
+    advance_secs(&mut contract, 1);
+    execute(&mut contract, client(), Operation::ReopenJob { job_id }).unwrap();
+
+    let reopened = job(&contract, job_id);
+    assert_eq!(reopened.status, JobStatus::Posted);
+    assert_eq!(reopened.agent, None);
+    assert!(reopened.bids.iter().all(|b| b.agent != agent()));
+    assert_eq!(agent_profile(&contract, agent()).active_jobs, 0);
+    // The full payment is held again and the no-show's stake went to the client
+    assert_eq!(escrow_held(&contract, job_id), tokens(100));
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 100 + 10)
+    );
+
+    // The client can now pick another bid
+    accept_bid(&mut contract, job_id, other_agent(), tokens(80)).unwrap();
+}
+
+// ==================== AGENTS ====================
+
+fn agent_ratings(contract: &JobMarketplaceContract, agent: AccountOwner) -> Vec<u64> {
+    contract
+        .state
+        .agent_ratings()
+        .get(&agent)
+        .blocking_wait()
+        .expect("Failed to read agent ratings")
+        .unwrap_or_default()
+}
+
+#[test]
+fn deregistering_withdraws_bids_and_clears_history() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+    let finished_job = post_job(&mut contract, tokens(100));
+    start_job(&mut contract, finished_job, agent());
+    execute(
+        &mut contract,
+        client(),
+        Operation::CompleteJob {
+            job_id: finished_job,
+        },
+    )
+    .unwrap();
+    execute(
+        &mut contract,
+        client(),
+        Operation::RateAgent {
+            job_id: finished_job,
+            rating: 5,
+            review: "Great".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(agent_jobs(&contract, agent()), vec![finished_job]);
+    assert_eq!(agent_ratings(&contract, agent()).len(), 1);
+
+    let open_job = post_job(&mut contract, tokens(100));
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(open_job, tokens(90), tokens(10)),
+    )
+    .unwrap();
+    let agent_balance = balance(&mut contract, agent());
+
+    execute(&mut contract, agent(), Operation::DeregisterAgent).unwrap();
+
+    assert!(job(&contract, open_job).bids.is_empty());
+    assert!(open_bids(&contract, agent()).is_empty());
+    assert_eq!(
+        balance(&mut contract, agent()),
+        agent_balance.saturating_add(tokens(10))
+    );
+    // Bids on jobs that were already awarded are left as they were
+    assert_eq!(job(&contract, finished_job).bids.len(), 1);
+    assert!(agent_jobs(&contract, agent()).is_empty());
+    assert!(agent_ratings(&contract, agent()).is_empty());
+}
+
+#[test]
+fn completed_jobs_are_recorded_in_the_agent_history() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+    let first = post_job(&mut contract, tokens(100));
+    let second = post_job(&mut contract, tokens(100));
+
+    for job_id in [first, second] {
+        start_job(&mut contract, job_id, agent());
+        execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    }
+
+    assert_eq!(agent_jobs(&contract, agent()), vec![first, second]);
+    assert_eq!(agent_profile(&contract, agent()).jobs_completed, 2);
+}
+
+#[test]
+fn agent_text_must_be_present_and_bounded() {
+    let mut contract = create_contract();
+    let register = |name: String| Operation::RegisterAgent {
+        name,
+        service_description: "Builds models".to_string(),
+        skills: vec![],
+        hourly_rate: None,
+    };
+
+    let empty = execute(&mut contract, agent(), register(String::new()));
+    assert!(matches!(empty, Err(JobMarketplaceError::InvalidInput)));
+    let too_long = execute(
+        &mut contract,
+        agent(),
+        register("x".repeat(MAX_NAME_LENGTH + 1)),
+    );
+    assert!(matches!(too_long, Err(JobMarketplaceError::InputTooLong)));
+    execute(&mut contract, agent(), register("Agent".to_string())).unwrap();
+
+    let update = execute(
+        &mut contract,
+        agent(),
+        Operation::UpdateAgentProfile {
+            name: None,
+            service_description: Some("x".repeat(MAX_DESCRIPTION_LENGTH + 1)),
+            skills: None,
+            portfolio_urls: None,
+            hourly_rate: None,
+            availability: None,
+        },
+    );
+    assert!(matches!(update, Err(JobMarketplaceError::InputTooLong)));
+    assert_eq!(
+        agent_profile(&contract, agent()).service_description,
+        "Builds models"
+    );
+}
+
+#[test]
+fn agents_toggle_their_availability() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+    assert!(agent_profile(&contract, agent()).availability);
+
+    execute(
+        &mut contract,
+        agent(),
+        Operation::SetAvailability { available: false },
+    )
+    .unwrap();
+    assert!(!agent_profile(&contract, agent()).availability);
+
+    execute(
+        &mut contract,
+        agent(),
+        Operation::SetAvailability { available: true },
+    )
+    .unwrap();
+    assert!(agent_profile(&contract, agent()).availability);
+
+    let unregistered = execute(
+        &mut contract,
+        other_agent(),
+        Operation::SetAvailability { available: false },
+    );
+    assert!(matches!(
+        unregistered,
+        Err(JobMarketplaceError::AgentNotRegistered)
+    ));
+}
+
+#[test]
+fn agents_cannot_deregister_with_active_jobs() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+
+    let result = execute(&mut contract, agent(), Operation::DeregisterAgent);
+    assert!(matches!(result, Err(JobMarketplaceError::HasActiveJobs)));
+    assert_eq!(agent_profile(&contract, agent()).active_jobs, 1);
+
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    execute(&mut contract, agent(), Operation::DeregisterAgent).unwrap();
+
+    assert!(!contract
+        .state
+        .agents()
+        .contains_key(&agent())
+        .blocking_wait()
+        .unwrap());
+}
+
+#[test]
+fn jobs_are_only_awarded_to_registered_agents() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(90), Amount::ZERO),
+    )
+    .unwrap();
+    // Drop the profile directly, so the bid is still in place
+    contract.state.agents_mut().remove(&agent()).unwrap();
+
+    let result = accept_bid(&mut contract, job_id, agent(), tokens(90));
+
+    assert!(matches!(
+        result,
+        Err(JobMarketplaceError::AgentNotRegistered)
+    ));
+    assert_eq!(job(&contract, job_id).status, JobStatus::Posted);
+}
+
+// ==================== JOB UPDATES ====================
+
+#[test]
+fn bids_and_edits_mark_the_job_updated() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    let posted_at = job(&contract, job_id).updated_at;
+
+    advance_secs(&mut contract, 60);
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(90), Amount::ZERO),
+    )
+    .unwrap();
+    let bid_at = job(&contract, job_id).updated_at;
+    assert!(bid_at > posted_at);
+
+    advance_secs(&mut contract, 60);
+    execute(
+        &mut contract,
+        client(),
+        Operation::EditJob {
+            job_id,
+            description: Some("Now with tests".to_string()),
+            payment: None,
+        },
+    )
+    .unwrap();
+    assert!(job(&contract, job_id).updated_at > bid_at);
+}
+
+// ==================== DEADLINES ====================
+
+#[test]
+fn posted_jobs_expire_only_after_their_deadline() {
+    let mut contract = create_contract();
+    let mut operation = post_job_operation(tokens(100));
+    if let Operation::PostJob { deadline, .. } = &mut operation {
+        *deadline = Some(START_SECS + 60);
+    }
+    let job_id = post_job_with(&mut contract, operation);
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(90), tokens(10)),
+    )
+    .unwrap();
+
+    // Anyone may expire the job, but not before the deadline
+    let early = execute(
+        &mut contract,
+        other_agent(),
+        Operation::ExpireJob { job_id },
+    );
+    assert!(matches!(
+        early,
+        Err(JobMarketplaceError::DeadlineNotReached)
+    ));
+    assert_eq!(job(&contract, job_id).status, JobStatus::Posted);
+
+    advance_secs(&mut contract, 61);
+    execute(
+        &mut contract,
+        other_agent(),
+        Operation::ExpireJob { job_id },
+    )
+    .unwrap();
+
+    assert_eq!(job(&contract, job_id).status, JobStatus::Expired);
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+    assert_eq!(balance(&mut contract, agent()), tokens(STARTING_BALANCE));
+    assert_eq!(escrow_held(&contract, job_id), Amount::ZERO);
+}
+
+#[test]
+fn unrepresentable_deadlines_are_rejected() {
+    let mut contract = create_contract();
+
+    for (deadline, auto_accept_at) in [
+        (Some(u64::MAX), None),
+        (None, Some(u64::MAX / 1_000_000 + 1)),
+    ] {
+        let mut operation = post_job_operation(tokens(100));
+        if let Operation::PostJob {
+            deadline: deadline_slot,
+            auto_accept_at: auto_accept_slot,
+            ..
+        } = &mut operation
+        {
+            *deadline_slot = deadline;
+            *auto_accept_slot = auto_accept_at;
+        }
+        let result = execute(&mut contract, client(), operation);
+        assert!(matches!(result, Err(JobMarketplaceError::InvalidDeadline)));
+    }
+
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+    assert_eq!(*contract.state.next_job_id().get(), 1);
+}
+
+#[test]
+fn unrepresentable_milestone_due_dates_are_rejected() {
+    let mut contract = create_contract();
+    let mut operation = milestone_job_operation(tokens(100), &[100]);
+    if let Operation::PostJob { milestones, .. } = &mut operation {
+        milestones[0].due_days = Some(u32::MAX);
+    }
+
+    let result = execute(&mut contract, client(), operation);
+
+    assert!(matches!(result, Err(JobMarketplaceError::InvalidDeadline)));
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+}
+
+// ==================== MILESTONES ====================
+
+#[test]
+fn milestone_percentages_must_total_one_hundred() {
+    let mut contract = create_contract();
+
+    let result = execute(
+        &mut contract,
+        client(),
+        milestone_job_operation(tokens(100), &[50, 40]),
+    );
+
+    assert!(matches!(
+        result,
+        Err(JobMarketplaceError::InvalidMilestonePercentages)
+    ));
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+}
+
+#[test]
+fn overflowing_milestone_percentages_are_rejected() {
+    let mut contract = create_contract();
+
+    // 156 + 200 wraps around to 100 in a u8
+    let result = execute(
+        &mut contract,
+        client(),
+        milestone_job_operation(tokens(100), &[156, 200]),
+    );
+
+    assert!(matches!(
+        result,
+        Err(JobMarketplaceError::InvalidMilestonePercentages)
+    ));
+}
+
+#[test]
+fn zero_percent_milestones_are_rejected() {
+    let mut contract = create_contract();
+
+    let result = execute(
+        &mut contract,
+        client(),
+        milestone_job_operation(tokens(100), &[100, 0]),
+    );
+
+    assert!(matches!(
+        result,
+        Err(JobMarketplaceError::InvalidMilestonePercentages)
+    ));
+}
+
+#[test]
+fn released_milestones_pay_out_the_whole_payment() {
+    let mut contract = create_contract();
+    let payment = Amount::from_attos(1_000_000_000_000_000_001);
+    let job_id = post_job_with(
+        &mut contract,
+        milestone_job_operation(payment, &[33, 33, 34]),
+    );
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+
+    for milestone_id in 0..3 {
+        execute(
+            &mut contract,
+            agent(),
+            Operation::SubmitMilestone {
+                job_id,
+                milestone_id,
+                delivery_notes: "Done".to_string(),
+            },
+        )
+        .unwrap();
+        execute(
+            &mut contract,
+            client(),
+            Operation::ApproveMilestone {
+                job_id,
+                milestone_id,
+            },
+        )
+        .unwrap();
+        execute(
+            &mut contract,
+            client(),
+            Operation::ReleaseMilestone {
+                job_id,
+                milestone_id,
+            },
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE).saturating_add(payment)
+    );
+    assert_eq!(escrow_held(&contract, job_id), Amount::ZERO);
+    assert_eq!(job(&contract, job_id).status, JobStatus::Completed);
+}
+
+// ==================== RATINGS ====================
+
+fn rate_agent(contract: &mut JobMarketplaceContract, job_id: u64, rating: u8) -> OperationResult {
+    execute(
+        contract,
+        client(),
+        Operation::RateAgent {
+            job_id,
+            rating,
+            review: "Review".to_string(),
+        },
+    )
+}
+
+/// Complete a new job for `agent`, have the client rate it and return the job ID
+fn complete_and_rate(
+    contract: &mut JobMarketplaceContract,
+    agent: AccountOwner,
+    rating: u8,
+) -> u64 {
+    let job_id = post_job(contract, tokens(100));
+    start_job(contract, job_id, agent);
+    execute(contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    rate_agent(contract, job_id, rating).unwrap();
+    job_id
+}
+
+#[test]
+fn a_job_can_only_be_rated_once() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+    let job_id = complete_and_rate(&mut contract, agent(), 5);
+
+    let result = rate_agent(&mut contract, job_id, 1);
+
+    assert!(matches!(result, Err(JobMarketplaceError::AlreadyRated)));
+    let profile = agent_profile(&contract, agent());
+    assert_eq!(profile.total_rating_points, 5);
+    assert_eq!(profile.total_ratings, 1);
+    assert_eq!(agent_ratings(&contract, agent()).len(), 1);
+}
+
+#[test]
+fn ratings_update_the_cached_average() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+
+    complete_and_rate(&mut contract, agent(), 3);
+    complete_and_rate(&mut contract, agent(), 5);
+
+    let profile = agent_profile(&contract, agent());
+    assert_eq!(profile.total_rating_points, 8);
+    assert_eq!(profile.total_ratings, 2);
+    assert_eq!(profile.average_rating, 4.0);
+}
+
+#[test]
+fn clients_and_agents_rate_each_other_independently() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+    let job_id = complete_and_rate(&mut contract, agent(), 2);
+    let rate_client = |rating| Operation::RateClient {
+        job_id,
+        rating,
+        review: "Paid on time".to_string(),
+    };
+
+    execute(&mut contract, agent(), rate_client(4)).unwrap();
+    let again = execute(&mut contract, agent(), rate_client(5));
+    assert!(matches!(again, Err(JobMarketplaceError::AlreadyRated)));
+
+    let client_profile = contract
+        .state
+        .clients()
+        .get(&client())
+        .blocking_wait()
+        .unwrap()
+        .expect("Client profile not found");
+    assert_eq!(client_profile.total_ratings, 1);
+    assert_eq!(client_profile.average_rating, 4.0);
+    let agent_profile = agent_profile(&contract, agent());
+    assert_eq!(agent_profile.total_ratings, 1);
+    assert_eq!(agent_profile.average_rating, 2.0);
+}
+
+#[test]
+fn older_ratings_weigh_less() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+
+    complete_and_rate(&mut contract, agent(), 1);
+    advance_secs(&mut contract, RATING_HALF_LIFE.as_micros() / 1_000_000);
+    complete_and_rate(&mut contract, agent(), 5);
+
+    // The older rating counts half as much: (1 * 1 + 5 * 2) / 3
+    let profile = agent_profile(&contract, agent());
+    assert_eq!(profile.average_rating, 3.0);
+    assert!((profile.weighted_score - 11.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn rating_weights_start_from_instantiation() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+
+    complete_and_rate(&mut contract, agent(), 4);
+
+    // A payment of 100 tokens rated at instantiation time has a weight of exactly 100
+    let profile = agent_profile(&contract, agent());
+    assert_eq!(profile.weighted_rating_total, 100.0);
+    assert_eq!(profile.weighted_rating_points, 400.0);
+}
+
+// ==================== EVENTS ====================
+
+/// Index the next job event will get, found by emitting a throwaway event
+fn next_event_index(contract: &mut JobMarketplaceContract) -> u32 {
+    let probe = JobEvent::JobStatusChanged {
+        job_id: 0,
+        status: JobStatus::Posted,
+    };
+    contract
+        .runtime
+        .emit(StreamName::from(JOB_EVENTS_STREAM), &probe)
+        + 1
+}
+
+#[test]
+fn releasing_payment_emits_payment_released() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    advance_secs(&mut contract, DISPUTE_WINDOW.as_micros() / 1_000_000 + 1);
+
+    let before = next_event_index(&mut contract);
+    execute(&mut contract, agent(), Operation::ReleasePayment { job_id }).unwrap();
+    let after = next_event_index(&mut contract);
+
+    // PaymentReleased is the only event, since the job stays completed
+    assert_eq!(after - before, 2);
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE + 100)
+    );
+}
+
+// ==================== STAKES ====================
+
+fn bid_stake(
+    contract: &JobMarketplaceContract,
+    job_id: u64,
+    agent: AccountOwner,
+) -> Option<Amount> {
+    contract
+        .state
+        .bid_stakes()
+        .get(&(job_id, agent))
+        .blocking_wait()
+        .expect("Failed to read bid stake")
+}
+
+#[test]
+fn losing_bids_have_their_stake_cleared() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    register_agent(&mut contract, other_agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(100), tokens(10)),
+    )
+    .unwrap();
+    execute(
+        &mut contract,
+        other_agent(),
+        bid_operation(job_id, tokens(90), tokens(10)),
+    )
+    .unwrap();
+
+    accept_bid(&mut contract, job_id, agent(), tokens(100)).unwrap();
+
+    let bids = job(&contract, job_id).bids;
+    let winner = bids.iter().find(|b| b.agent == agent()).unwrap();
+    let loser = bids.iter().find(|b| b.agent == other_agent()).unwrap();
+    assert_eq!(winner.stake, tokens(10));
+    assert_eq!(loser.stake, Amount::ZERO);
+    assert_eq!(bid_stake(&contract, job_id, other_agent()), None);
+    assert_eq!(
+        balance(&mut contract, other_agent()),
+        tokens(STARTING_BALANCE)
+    );
+}
+
+#[test]
+fn jobs_are_reassigned_only_after_the_inactivity_period() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, other_agent());
+    execute(
+        &mut contract,
+        other_agent(),
+        bid_operation(job_id, tokens(90), Amount::ZERO),
+    )
+    .unwrap();
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+    let reassign = || Operation::ReassignJob {
+        job_id,
+        new_agent: other_agent(),
+    };
+
+    let reassign_after_secs = parameters().reassign_after_secs;
+    advance_secs(&mut contract, reassign_after_secs);
+    let early = execute(&mut contract, client(), reassign());
+    assert!(matches!(early, Err(JobMarketplaceError::ReassignTooEarly)));
+    assert_eq!(job(&contract, job_id).agent, Some(agent()));
+
+    advance_secs(&mut contract, 1);
+    execute(&mut contract, client(), reassign()).unwrap();
+
+    let job = job(&contract, job_id);
+    assert_eq!(job.status, JobStatus::AwaitingConfirmation);
+    assert_eq!(job.agent, Some(other_agent()));
+    assert_eq!(job.accepted_bid_amount, Some(tokens(90)));
+    assert_eq!(escrow_held(&contract, job_id), tokens(90));
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE - 90)
+    );
+    assert_eq!(agent_profile(&contract, agent()).active_jobs, 0);
+    assert_eq!(agent_profile(&contract, other_agent()).active_jobs, 1);
+}
+
+#[test]
+fn reassigned_agent_locks_a_stake_on_confirming() {
+    let mut contract = create_contract_with(MarketplaceParameters {
+        min_bid_stake: tokens(5),
+        ..parameters()
+    });
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, other_agent());
+    execute(
+        &mut contract,
+        other_agent(),
+        bid_operation(job_id, tokens(100), tokens(5)),
+    )
+    .unwrap();
+    register_agent(&mut contract, agent());
+    start_job_with_stake(&mut contract, job_id, agent(), tokens(5));
+    assert_eq!(bid_stake(&contract, job_id, other_agent()), None);
+
+    let reassign_after_secs = parameters().reassign_after_secs;
+    advance_secs(&mut contract, reassign_after_secs + 1);
+    execute(
+        &mut contract,
+        client(),
+        Operation::ReassignJob {
+            job_id,
+            new_agent: other_agent(),
+        },
+    )
+    .unwrap();
+    execute(
+        &mut contract,
+        other_agent(),
+        Operation::ConfirmJob { job_id },
+    )
+    .unwrap();
+
+    assert_eq!(bid_stake(&contract, job_id, other_agent()), Some(tokens(5)));
+    assert_eq!(
+        balance(&mut contract, other_agent()),
+        tokens(STARTING_BALANCE - 5)
+    );
+    // The previous agent's stake went to the client
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE - 5)
+    );
+    assert_eq!(job(&contract, job_id).status, JobStatus::InProgress);
+}
+
+#[test]
+fn abandoned_jobs_refund_the_client_and_slash_the_stake() {
+    let mut contract = create_contract();
+    let mut operation = post_job_operation(tokens(100));
+    if let Operation::PostJob { deadline, .. } = &mut operation {
+        *deadline = Some(START_SECS + 60);
+    }
+    let job_id = post_job_with(&mut contract, operation);
+    register_agent(&mut contract, agent());
+    start_job_with_stake(&mut contract, job_id, agent(), tokens(10));
+
+    let early = execute(&mut contract, client(), Operation::AbandonJob { job_id });
+    assert!(matches!(
+        early,
+        Err(JobMarketplaceError::DeadlineNotReached)
+    ));
+
+    advance_secs(&mut contract, 61);
+    execute(&mut contract, client(), Operation::AbandonJob { job_id }).unwrap();
+
+    assert_eq!(job(&contract, job_id).status, JobStatus::Cancelled);
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE + 10)
+    );
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE - 10)
+    );
+    assert_eq!(bid_stake(&contract, job_id, agent()), None);
+    assert_eq!(agent_profile(&contract, agent()).active_jobs, 0);
+}
+
+// ==================== DISPUTES ====================
+
+/// Open a dispute on the job as the client and return its ID
+fn open_dispute(contract: &mut JobMarketplaceContract, job_id: u64) -> u64 {
+    let dispute_id = *contract.state.next_dispute_id().get();
+    execute(
+        contract,
+        client(),
+        Operation::OpenDispute {
+            job_id,
+            reason: "Work is missing".to_string(),
+        },
+    )
+    .expect("Failed to open dispute");
+    dispute_id
+}
+
+fn resolve_dispute(
+    contract: &mut JobMarketplaceContract,
+    dispute_id: u64,
+    resolution: DisputeStatus,
+) -> OperationResult {
+    execute(
+        contract,
+        admin(),
+        Operation::ResolveDispute {
+            dispute_id,
+            resolution,
+            refund_percentage: None,
+            notes: "Resolved".to_string(),
+        },
+    )
+}
+
+#[test]
+fn completed_job_refunded_in_dispute_is_no_longer_credited() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job_with_stake(&mut contract, job_id, agent(), tokens(10));
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    assert_eq!(agent_profile(&contract, agent()).jobs_completed, 1);
+    assert_eq!(agent_jobs(&contract, agent()), vec![job_id]);
+
+    let dispute_id = open_dispute(&mut contract, job_id);
+    resolve_dispute(&mut contract, dispute_id, DisputeStatus::ResolvedForClient).unwrap();
+
+    let profile = agent_profile(&contract, agent());
+    assert_eq!(profile.jobs_completed, 0);
+    assert_eq!(profile.success_rate, 100);
+    assert_eq!(profile.disputes_involved, 1);
+    assert_eq!(profile.disputes_lost, 1);
+    assert!(agent_jobs(&contract, agent()).is_empty());
+    assert_eq!(job(&contract, job_id).completed_at, None);
+    // The client gets both the payment and the forfeited stake back
+    assert_eq!(
+        balance(&mut contract, client()),
+        tokens(STARTING_BALANCE + 10)
+    );
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE - 10)
+    );
+}
+
+#[test]
+fn disputed_job_resolved_for_agent_counts_once() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job(&mut contract, job_id, agent());
+
+    let dispute_id = open_dispute(&mut contract, job_id);
+    resolve_dispute(&mut contract, dispute_id, DisputeStatus::ResolvedForAgent).unwrap();
+
+    let profile = agent_profile(&contract, agent());
+    assert_eq!(profile.jobs_completed, 1);
+    assert_eq!(profile.disputes_involved, 1);
+    assert_eq!(profile.disputes_lost, 0);
+    assert_eq!(agent_jobs(&contract, agent()), vec![job_id]);
+}
+
+#[test]
+fn stake_is_held_until_payment_is_released() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job_with_stake(&mut contract, job_id, agent(), tokens(10));
+
+    execute(&mut contract, client(), Operation::CompleteJob { job_id }).unwrap();
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE - 10)
+    );
+
+    advance_secs(&mut contract, DISPUTE_WINDOW.as_micros() / 1_000_000 + 1);
+    execute(&mut contract, agent(), Operation::ReleasePayment { job_id }).unwrap();
+
+    assert_eq!(
+        balance(&mut contract, agent()),
+        tokens(STARTING_BALANCE + 100)
+    );
+    assert_eq!(escrow_account_balance(&mut contract), Amount::ZERO);
+}
+
+// ==================== JOB BOARD ====================
+
+#[test]
+fn posted_jobs_are_announced_to_the_job_board() {
+    let board = ChainId(CryptoHash::test_hash("job-board"));
+    let board_parameters = MarketplaceParameters {
+        job_board_chain_id: Some(board),
+        ..parameters()
+    };
+    let mut client_chain = create_contract_with(board_parameters.clone());
+    let origin = client_chain.runtime.chain_id();
+
+    let job_id = post_job(&mut client_chain, tokens(100));
+
+    let request = {
+        let mut requests = client_chain.runtime.created_send_message_requests();
+        assert_eq!(requests.len(), 1);
+        requests.pop().unwrap()
+    };
+    assert_eq!(request.destination, board);
+    assert!(matches!(request.message, Message::JobPosted { .. }));
+
+    let mut job_board = create_contract_with(board_parameters);
+    job_board.runtime.set_chain_id(board);
+    job_board.runtime.set_message_origin_chain_id(origin);
+    job_board.execute_message(request.message).blocking_wait();
+
+    let remote_job = job_board
+        .state
+        .remote_jobs()
+        .get(&(origin, job_id))
+        .blocking_wait()
+        .unwrap()
+        .expect("Remote job not recorded");
+    assert_eq!(remote_job.title, "Train a model");
+    assert_eq!(remote_job.payment, tokens(100));
+
+    // Jobs posted on the job board itself are not announced
+    post_job(&mut job_board, tokens(100));
+    assert!(job_board.runtime.created_send_message_requests().is_empty());
+}
+
+// ==================== STATISTICS ====================
+
+/// Check the maintained status counters against a recount of every job
+fn assert_status_counts_match(contract: &JobMarketplaceContract) {
+    let jobs: Vec<Job> = (1..*contract.state.next_job_id().get())
+        .map(|job_id| job(contract, job_id))
+        .collect();
+    let mut counted = 0;
+    contract
+        .state
+        .status_counts()
+        .for_each_index_value(|status, count| {
+            let actual = jobs.iter().filter(|job| job.status == status).count() as u64;
+            assert_eq!(*count, actual, "Wrong count for {status:?}");
+            counted += *count;
+            Ok(())
+        })
+        .blocking_wait()
+        .expect("Failed to read status counts");
+    assert_eq!(counted, jobs.len() as u64);
+}
+
+#[test]
+fn status_counts_follow_every_transition() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+    let cancelled = post_job(&mut contract, tokens(100));
+    let completed = post_job(&mut contract, tokens(100));
+    let in_progress = post_job(&mut contract, tokens(100));
+    let mut operation = post_job_operation(tokens(100));
+    if let Operation::PostJob { deadline, .. } = &mut operation {
+        *deadline = Some(START_SECS + 60);
+    }
+    let expired = post_job_with(&mut contract, operation);
+    post_job(&mut contract, tokens(100));
+    assert_status_counts_match(&contract);
+
+    execute(
+        &mut contract,
+        client(),
+        Operation::CancelJob { job_id: cancelled },
+    )
+    .unwrap();
+    start_job(&mut contract, completed, agent());
+    execute(
+        &mut contract,
+        client(),
+        Operation::CompleteJob { job_id: completed },
+    )
+    .unwrap();
+    start_job(&mut contract, in_progress, agent());
+    advance_secs(&mut contract, 61);
+    execute(
+        &mut contract,
+        client(),
+        Operation::ExpireJob { job_id: expired },
+    )
+    .unwrap();
+
+    assert_status_counts_match(&contract);
+    assert_eq!(
+        contract
+            .state
+            .status_counts()
+            .get(&JobStatus::Posted)
+            .blocking_wait()
+            .unwrap(),
+        Some(1)
+    );
+}
+
+#[test]
+fn payment_volume_overflow_is_flagged_without_failing_the_job() {
+    let mut contract = create_contract();
+    contract
+        .state
+        .total_payment_volume_mut()
+        .set(Amount::MAX.saturating_sub(tokens(50)));
+
+    let job_id = post_job(&mut contract, tokens(100));
+
+    assert_eq!(job(&contract, job_id).payment, tokens(100));
+    assert!(*contract.state.payment_volume_overflowed().get());
+    assert_eq!(
+        *contract.state.total_payment_volume().get(),
+        Amount::MAX.saturating_sub(tokens(50))
+    );
+}
+
+#[test]
+fn payment_volume_tracks_posted_and_edited_jobs() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+
+    edit_payment(&mut contract, job_id, tokens(60)).unwrap();
+
+    assert_eq!(*contract.state.total_payment_volume().get(), tokens(60));
+    assert!(!*contract.state.payment_volume_overflowed().get());
+}
+
+// ==================== MODERATION ====================
+
+#[test]
+fn removing_a_job_refunds_the_escrow_and_stakes() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(90), tokens(10)),
+    )
+    .unwrap();
+
+    let by_client = execute(
+        &mut contract,
+        client(),
+        Operation::AdminRemoveJob { job_id },
+    );
+    assert!(matches!(by_client, Err(JobMarketplaceError::NotAuthorized)));
+
+    execute(&mut contract, admin(), Operation::AdminRemoveJob { job_id }).unwrap();
+
+    assert!(contract
+        .state
+        .jobs()
+        .get(&job_id)
+        .blocking_wait()
+        .unwrap()
+        .is_none());
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+    assert_eq!(balance(&mut contract, agent()), tokens(STARTING_BALANCE));
+    assert_eq!(escrow_account_balance(&mut contract), Amount::ZERO);
+    assert_eq!(*contract.state.total_bids().get(), 0);
+}
+
+#[test]
+fn removing_a_running_job_frees_the_agent() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    start_job_with_stake(&mut contract, job_id, agent(), tokens(10));
+
+    execute(&mut contract, admin(), Operation::AdminRemoveJob { job_id }).unwrap();
+
+    assert_eq!(agent_profile(&contract, agent()).active_jobs, 0);
+    assert_eq!(balance(&mut contract, client()), tokens(STARTING_BALANCE));
+    assert_eq!(balance(&mut contract, agent()), tokens(STARTING_BALANCE));
+    assert_eq!(escrow_account_balance(&mut contract), Amount::ZERO);
+}
+
+#[test]
+fn banning_an_agent_withdraws_their_open_bids() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(90), tokens(10)),
+    )
+    .unwrap();
+
+    execute(
+        &mut contract,
+        admin(),
+        Operation::BanAgent { agent: agent() },
+    )
+    .unwrap();
+
+    assert!(job(&contract, job_id).bids.is_empty());
+    assert_eq!(*contract.state.total_bids().get(), 0);
+    assert_eq!(balance(&mut contract, agent()), tokens(STARTING_BALANCE));
+    assert!(matches!(
+        accept_bid(&mut contract, job_id, agent(), tokens(90)),
+        Err(JobMarketplaceError::BidNotFound)
+    ));
+}
+
+#[test]
+fn open_bids_only_list_jobs_still_taking_bids() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+    register_agent(&mut contract, other_agent());
+    let awarded = post_job(&mut contract, tokens(100));
+    let cancelled = post_job(&mut contract, tokens(100));
+    let withdrawn = post_job(&mut contract, tokens(100));
+    let open = post_job(&mut contract, tokens(100));
+    for job_id in [awarded, cancelled, withdrawn, open] {
+        place_bid(&mut contract, agent(), job_id, tokens(90));
+    }
+    place_bid(&mut contract, other_agent(), awarded, tokens(80));
+    assert_eq!(
+        open_bids(&contract, agent()),
+        vec![awarded, cancelled, withdrawn, open]
+    );
+
+    accept_bid(&mut contract, awarded, other_agent(), tokens(80)).unwrap();
+    execute(
+        &mut contract,
+        client(),
+        Operation::CancelJob { job_id: cancelled },
+    )
+    .unwrap();
+    execute(
+        &mut contract,
+        agent(),
+        Operation::WithdrawBid { job_id: withdrawn },
+    )
+    .unwrap();
+    assert_eq!(open_bids(&contract, agent()), vec![open]);
+    assert!(open_bids(&contract, other_agent()).is_empty());
+
+    execute(
+        &mut contract,
+        admin(),
+        Operation::BanAgent { agent: agent() },
+    )
+    .unwrap();
+
+    assert!(open_bids(&contract, agent()).is_empty());
+    assert!(job(&contract, open).bids.is_empty());
+    // The rejected bid on the awarded job is history, not an open bid
+    assert_eq!(job(&contract, awarded).bids.len(), 2);
+}
+
+#[test]
+fn reopened_jobs_are_open_bids_again() {
+    let mut contract = create_contract();
+    register_agent(&mut contract, agent());
+    register_agent(&mut contract, other_agent());
+    let job_id = post_job(&mut contract, tokens(100));
+    place_bid(&mut contract, agent(), job_id, tokens(90));
+    place_bid(&mut contract, other_agent(), job_id, tokens(80));
+    accept_bid(&mut contract, job_id, agent(), tokens(90)).unwrap();
+    assert!(open_bids(&contract, other_agent()).is_empty());
+
+    advance_secs(&mut contract, parameters().confirm_within_secs + 1);
+    execute(&mut contract, client(), Operation::ReopenJob { job_id }).unwrap();
+
+    assert_eq!(open_bids(&contract, other_agent()), vec![job_id]);
+    assert!(open_bids(&contract, agent()).is_empty());
+}
+
+#[test]
+fn banned_agents_cannot_be_awarded_a_job() {
+    let mut contract = create_contract();
+    let job_id = post_job(&mut contract, tokens(100));
+    register_agent(&mut contract, agent());
+    execute(
+        &mut contract,
+        agent(),
+        bid_operation(job_id, tokens(90), Amount::ZERO),
+    )
+    .unwrap();
+    // Ban without going through BanAgent, so the bid is still in place
+    contract.state.banned_mut().insert(&agent(), true).unwrap();
+
+    let result = accept_bid(&mut contract, job_id, agent(), tokens(90));
+
+    assert!(matches!(result, Err(JobMarketplaceError::AgentBanned)));
+    assert_eq!(job(&contract, job_id).status, JobStatus::Posted);
+}
@@ -0,0 +1,1047 @@
+/*!
+Unit tests for the job marketplace GraphQL service, run against the mock runtime
+*/
+
+use async_graphql::{Request, Response};
+use job_marketplace::{
+    AgentProfile, AgentRating, Bid, ClientProfile, Job, JobCategory, JobMarketplace, JobStatus,
+    MarketplaceParameters, VerificationLevel,
+};
+use linera_sdk::{
+    linera_base_types::{AccountOwner, Amount, CryptoHash, Timestamp},
+    serde_json::{json, Value},
+    util::BlockingWait,
+    views::{RootView, View, ViewStorageContext},
+    Service, ServiceRuntime,
+};
+
+use super::JobMarketplaceService;
+
+/// System time the queries run at, in seconds
+const NOW_SECS: u64 = 1_700_000_000;
+
+// ==================== FIXTURES ====================
+
+fn owner(name: &str) -> AccountOwner {
+    CryptoHash::test_hash(name).into()
+}
+
+fn tokens(amount: u128) -> Amount {
+    Amount::from_tokens(amount)
+}
+
+fn parameters() -> MarketplaceParameters {
+    MarketplaceParameters {
+        fee_basis_points: 0,
+        fee_recipient: owner("fee-recipient"),
+        job_board_chain_id: None,
+        max_open_jobs_per_client: None,
+        min_bid_stake: Amount::ZERO,
+        max_active_jobs_per_agent: None,
+        reassign_after_secs: 7 * 24 * 60 * 60,
+        confirm_within_secs: 2 * 24 * 60 * 60,
+    }
+}
+
+fn at_secs(secs: u64) -> Timestamp {
+    Timestamp::from(secs * 1_000_000)
+}
+
+/// A posted job by `client`, last updated at `updated_secs`
+fn sample_job(id: u64, client: AccountOwner, updated_secs: u64) -> Job {
+    Job {
+        id,
+        client,
+        description: "Fine-tune a classifier".to_string(),
+        payment: tokens(100),
+        status: JobStatus::Posted,
+        agent: None,
+        bids: vec![],
+        created_at: at_secs(updated_secs),
+        title: format!("Job {id}"),
+        category: JobCategory::AIModel,
+        tags: vec![],
+        deadline: None,
+        milestones: vec![],
+        accepted_bid_amount: None,
+        escrow_id: None,
+        completed_at: None,
+        auto_accept_at: None,
+        updated_at: at_secs(updated_secs),
+        accepted_at: None,
+        settled: false,
+    }
+}
+
+/// Store jobs and advance the next job ID past them
+fn insert_jobs(state: &mut JobMarketplace, jobs: Vec<Job>) {
+    for job in jobs {
+        let job_id = job.id;
+        let next_id = (*state.next_job_id().get()).max(job_id + 1);
+        state.next_job_id_mut().set(next_id);
+        state
+            .jobs_mut()
+            .insert(&job_id, job)
+            .expect("Failed to insert job");
+    }
+}
+
+/// A freshly registered, available agent with no history
+fn sample_agent(owner: AccountOwner, name: &str) -> AgentProfile {
+    AgentProfile {
+        owner,
+        name: name.to_string(),
+        service_description: "Builds models".to_string(),
+        jobs_completed: 0,
+        total_rating_points: 0,
+        total_ratings: 0,
+        average_rating: 0.0,
+        weighted_rating_points: 0.0,
+        weighted_rating_total: 0.0,
+        weighted_score: 0.0,
+        registered_at: at_secs(NOW_SECS),
+        verification_level: VerificationLevel::Unverified,
+        skills: vec!["rust".to_string()],
+        portfolio_urls: vec![],
+        hourly_rate: None,
+        availability: true,
+        response_time_hours: 24,
+        success_rate: 100,
+        active_jobs: 0,
+        disputes_involved: 0,
+        disputes_lost: 0,
+    }
+}
+
+fn insert_agents(state: &mut JobMarketplace, agents: Vec<AgentProfile>) {
+    for agent in agents {
+        let owner = agent.owner;
+        state
+            .agents_mut()
+            .insert(&owner, agent)
+            .expect("Failed to insert agent");
+    }
+}
+
+/// Store ratings under consecutive IDs starting at 1, indexed by agent
+fn insert_ratings(state: &mut JobMarketplace, ratings: Vec<AgentRating>) {
+    for (rating_id, rating) in (1..).zip(ratings) {
+        let mut agent_ratings = state
+            .agent_ratings()
+            .get(&rating.agent)
+            .blocking_wait()
+            .expect("Failed to read agent ratings")
+            .unwrap_or_default();
+        agent_ratings.push(rating_id);
+        state
+            .agent_ratings_mut()
+            .insert(&rating.agent, agent_ratings)
+            .expect("Failed to index rating");
+        state
+            .ratings_mut()
+            .insert(&rating_id, rating)
+            .expect("Failed to insert rating");
+        state.next_rating_id_mut().set(rating_id + 1);
+    }
+}
+
+fn sample_rating(
+    job_id: u64,
+    agent: AccountOwner,
+    rater: AccountOwner,
+    rating: u8,
+    secs: u64,
+) -> AgentRating {
+    AgentRating {
+        job_id,
+        agent,
+        rater,
+        rating,
+        review: format!("Review of job {job_id}"),
+        timestamp: at_secs(secs),
+    }
+}
+
+fn ids(items: &Value) -> Vec<u64> {
+    items
+        .as_array()
+        .expect("Expected a list")
+        .iter()
+        .map(|item| item["id"].as_u64().expect("Expected an ID"))
+        .collect()
+}
+
+/// Create a service over state prepared by `populate`
+fn create_service(populate: impl FnOnce(&mut JobMarketplace)) -> JobMarketplaceService {
+    let runtime = ServiceRuntime::<JobMarketplaceService>::new()
+        .with_application_parameters(parameters())
+        .with_system_time(Timestamp::from(NOW_SECS * 1_000_000));
+
+    // The service only gets read access, so the state is written through a mutable store
+    let context =
+        ViewStorageContext::new_unchecked(runtime.key_value_store().to_mut(), Vec::new(), ());
+    let mut state = JobMarketplace::load(context)
+        .blocking_wait()
+        .expect("Failed to load state");
+    populate(&mut state);
+    state.save().blocking_wait().expect("Failed to save state");
+
+    JobMarketplaceService::new(runtime).blocking_wait()
+}
+
+fn query(service: &JobMarketplaceService, query: &str) -> Response {
+    service.handle_query(Request::new(query)).blocking_wait()
+}
+
+/// Run a query that must succeed and return its data as JSON
+fn query_data(service: &JobMarketplaceService, query_text: &str) -> Value {
+    let response = query(service, query_text);
+    assert!(
+        response.errors.is_empty(),
+        "Query failed: {:?}",
+        response.errors
+    );
+    response
+        .data
+        .into_json()
+        .expect("Response data is not JSON")
+}
+
+// ==================== AGENTS ====================
+
+#[test]
+fn agent_lookup_matches_the_exact_owner() {
+    let (first, second) = (owner("first"), owner("second"));
+    let service = create_service(|state| {
+        insert_agents(
+            state,
+            vec![sample_agent(first, "First"), sample_agent(second, "Second")],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ first: agent(owner: \"{first}\") {{ name }} \
+             second: agent(owner: \"{second}\") {{ name }} \
+             missing: agent(owner: \"{}\") {{ name }} }}",
+            owner("missing")
+        ),
+    );
+
+    assert_eq!(
+        data,
+        json!({
+            "first": { "name": "First" },
+            "second": { "name": "Second" },
+            "missing": null,
+        })
+    );
+}
+
+#[test]
+fn busy_agents_can_be_filtered_out() {
+    let service = create_service(|state| {
+        insert_agents(
+            state,
+            vec![
+                sample_agent(owner("free"), "Free"),
+                AgentProfile {
+                    availability: false,
+                    ..sample_agent(owner("busy"), "Busy")
+                },
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ available: agents(filter: { available: true }) { name } \
+         busy: agents(filter: { available: false }) { name } \
+         everyone: agentsCount }",
+    );
+
+    assert_eq!(
+        data,
+        json!({
+            "available": [{ "name": "Free" }],
+            "busy": [{ "name": "Busy" }],
+            "everyone": 2,
+        })
+    );
+}
+
+#[test]
+fn top_agents_ranks_only_agents_with_enough_ratings() {
+    let rated =
+        |name: &str, jobs_completed: u64, total_ratings: u64, average_rating: f64| AgentProfile {
+            jobs_completed,
+            total_ratings,
+            average_rating,
+            ..sample_agent(owner(name), name)
+        };
+    let service = create_service(|state| {
+        insert_agents(
+            state,
+            vec![
+                rated("lucky", 1, 1, 5.0),
+                rated("solid", 6, 4, 4.5),
+                rated("busy", 9, 3, 3.0),
+                rated("good", 4, 3, 4.0),
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ byRating: topAgents(by: RATING, limit: 2) { rank agent { name } } \
+         byJobs: topAgents(by: JOBS_COMPLETED, limit: 2) { rank agent { name } } }",
+    );
+
+    // A single 5-star rating is not enough to make the rating board
+    assert_eq!(
+        data,
+        json!({
+            "byRating": [
+                { "rank": 1, "agent": { "name": "solid" } },
+                { "rank": 2, "agent": { "name": "good" } },
+            ],
+            "byJobs": [
+                { "rank": 1, "agent": { "name": "busy" } },
+                { "rank": 2, "agent": { "name": "solid" } },
+            ],
+        })
+    );
+}
+
+#[test]
+fn dispute_rate_counts_each_finished_job_once() {
+    let agent = owner("agent");
+    let service = create_service(|state| {
+        insert_agents(
+            state,
+            vec![AgentProfile {
+                jobs_completed: 3,
+                disputes_involved: 2,
+                disputes_lost: 1,
+                ..sample_agent(agent, "Agent")
+            }],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        &format!("{{ agent(owner: \"{agent}\") {{ disputeRate }} }}"),
+    );
+
+    // Three completed jobs (one won in a dispute) plus one lost dispute
+    assert_eq!(data, json!({ "agent": { "disputeRate": 0.5 } }));
+}
+
+#[test]
+fn agents_sort_by_weighted_score() {
+    let service = create_service(|state| {
+        insert_agents(
+            state,
+            vec![
+                AgentProfile {
+                    weighted_rating_points: 3.0,
+                    weighted_rating_total: 1.0,
+                    weighted_score: 3.0,
+                    ..sample_agent(owner("steady"), "Steady")
+                },
+                AgentProfile {
+                    weighted_rating_points: 9.0,
+                    weighted_rating_total: 2.0,
+                    weighted_score: 4.5,
+                    ..sample_agent(owner("recent"), "Recent")
+                },
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ agents(sortBy: WEIGHTED_SCORE, sortDir: DESC) { name weightedScore } }",
+    );
+
+    assert_eq!(
+        data,
+        json!({ "agents": [
+            { "name": "Recent", "weightedScore": 4.5 },
+            { "name": "Steady", "weightedScore": 3.0 },
+        ] })
+    );
+}
+
+#[test]
+fn agents_are_filtered_and_sorted_by_average_rating() {
+    let (top, middle, low) = (owner("top"), owner("middle"), owner("low"));
+    let service = create_service(|state| {
+        insert_agents(
+            state,
+            vec![
+                AgentProfile {
+                    total_rating_points: 7,
+                    total_ratings: 2,
+                    average_rating: 3.5,
+                    ..sample_agent(middle, "Middle")
+                },
+                AgentProfile {
+                    total_rating_points: 5,
+                    total_ratings: 1,
+                    average_rating: 5.0,
+                    ..sample_agent(top, "Top")
+                },
+                AgentProfile {
+                    total_rating_points: 4,
+                    total_ratings: 2,
+                    average_rating: 2.0,
+                    ..sample_agent(low, "Low")
+                },
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ agents(filter: { minRating: 3.0 }, sortBy: RATING, sortDir: DESC) \
+         { name averageRating } }",
+    );
+
+    assert_eq!(
+        data,
+        json!({ "agents": [
+            { "name": "Top", "averageRating": 5.0 },
+            { "name": "Middle", "averageRating": 3.5 },
+        ] })
+    );
+}
+
+#[test]
+fn agent_completed_jobs_are_listed_newest_first() {
+    let (client, agent) = (owner("client"), owner("agent"));
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            (1..=3).map(|id| sample_job(id, client, NOW_SECS)).collect(),
+        );
+        state.agent_jobs_mut().insert(&agent, vec![1, 3]).unwrap();
+    });
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ all: agentCompletedJobs(owner: \"{agent}\") {{ id }} \
+             older: agentCompletedJobs(owner: \"{agent}\", offset: 1) {{ id }} \
+             none: agentCompletedJobs(owner: \"{client}\") {{ id }} }}"
+        ),
+    );
+
+    assert_eq!(ids(&data["all"]), vec![3, 1]);
+    assert_eq!(ids(&data["older"]), vec![1]);
+    assert_eq!(ids(&data["none"]), Vec::<u64>::new());
+}
+
+// ==================== RATINGS ====================
+
+#[test]
+fn ratings_by_rater_lists_a_clients_reviews_newest_first() {
+    let (client, other_client, agent) = (owner("client"), owner("other-client"), owner("agent"));
+    let service = create_service(|state| {
+        insert_ratings(
+            state,
+            vec![
+                sample_rating(1, agent, client, 4, NOW_SECS - 20),
+                sample_rating(2, agent, other_client, 1, NOW_SECS - 15),
+                sample_rating(3, agent, client, 5, NOW_SECS - 10),
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ all: ratingsByRater(rater: \"{client}\") {{ jobId rating }} \
+             latest: ratingsByRater(rater: \"{client}\", limit: 1) {{ jobId }} }}"
+        ),
+    );
+
+    assert_eq!(
+        data,
+        json!({
+            "all": [{ "jobId": 3, "rating": 5 }, { "jobId": 1, "rating": 4 }],
+            "latest": [{ "jobId": 3 }],
+        })
+    );
+}
+
+#[test]
+fn agent_profile_with_reviews_returns_the_latest_reviews() {
+    let (agent, other_agent, client) = (owner("agent"), owner("other-agent"), owner("client"));
+    let service = create_service(|state| {
+        insert_agents(state, vec![sample_agent(agent, "Agent")]);
+        insert_ratings(
+            state,
+            vec![
+                sample_rating(1, agent, client, 3, NOW_SECS - 30),
+                sample_rating(2, other_agent, client, 1, NOW_SECS - 25),
+                sample_rating(3, agent, client, 4, NOW_SECS - 20),
+                sample_rating(4, agent, client, 5, NOW_SECS - 10),
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ all: agentProfileWithReviews(owner: \"{agent}\") \
+               {{ profile {{ name }} recentReviews {{ jobId }} }} \
+             latest: agentProfileWithReviews(owner: \"{agent}\", reviewLimit: 2) \
+               {{ recentReviews {{ jobId rating }} }} \
+             unregistered: agentProfileWithReviews(owner: \"{other_agent}\") \
+               {{ recentReviews {{ jobId }} }} }}"
+        ),
+    );
+
+    assert_eq!(
+        data,
+        json!({
+            "all": {
+                "profile": { "name": "Agent" },
+                "recentReviews": [{ "jobId": 4 }, { "jobId": 3 }, { "jobId": 1 }],
+            },
+            "latest": {
+                "recentReviews": [{ "jobId": 4, "rating": 5 }, { "jobId": 3, "rating": 4 }],
+            },
+            "unregistered": null,
+        })
+    );
+}
+
+#[test]
+fn agent_ratings_only_lists_the_requested_agents_ratings() {
+    let (agent, other_agent, client) = (owner("agent"), owner("other-agent"), owner("client"));
+    let service = create_service(|state| {
+        insert_ratings(
+            state,
+            vec![
+                sample_rating(1, agent, client, 4, NOW_SECS - 20),
+                sample_rating(2, other_agent, client, 1, NOW_SECS - 15),
+                sample_rating(3, agent, client, 5, NOW_SECS - 10),
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ agent: agentRatings(agentOwner: \"{agent}\") {{ jobId agent }} \
+             unrated: agentRatings(agentOwner: \"{}\") {{ jobId }} }}",
+            owner("unrated")
+        ),
+    );
+
+    assert_eq!(
+        data,
+        json!({
+            "agent": [
+                { "jobId": 1, "agent": agent.to_string() },
+                { "jobId": 3, "agent": agent.to_string() },
+            ],
+            "unrated": [],
+        })
+    );
+}
+
+// ==================== CLIENTS ====================
+
+#[test]
+fn clients_are_filtered_and_sorted_by_average_rating() {
+    let client_profile = |name: &str, points: u64, ratings: u64| ClientProfile {
+        owner: owner(name),
+        total_rating_points: points,
+        total_ratings: ratings,
+        average_rating: points as f64 / ratings as f64,
+    };
+    let service = create_service(|state| {
+        for profile in [
+            client_profile("fair", 6, 2),
+            client_profile("great", 9, 2),
+            client_profile("new", 5, 1),
+        ] {
+            let owner = profile.owner;
+            state.clients_mut().insert(&owner, profile).unwrap();
+        }
+    });
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ clients(filter: {{ minRatings: 2 }}) {{ averageRating }} \
+             client(owner: \"{}\") {{ totalRatings averageRating }} }}",
+            owner("new")
+        ),
+    );
+
+    assert_eq!(
+        data,
+        json!({
+            "clients": [{ "averageRating": 4.5 }, { "averageRating": 3.0 }],
+            "client": { "totalRatings": 1, "averageRating": 5.0 },
+        })
+    );
+}
+
+// ==================== LOOKUPS ====================
+
+#[test]
+fn lookups_reject_malformed_owners() {
+    let service = create_service(|_| {});
+
+    for field in ["isBanned", "agentCompletedJobs", "ratingsByRater"] {
+        let argument = if field == "ratingsByRater" {
+            "rater"
+        } else {
+            "owner"
+        };
+        let selection = if field == "isBanned" {
+            ""
+        } else {
+            " { __typename }"
+        };
+        let response = query(
+            &service,
+            &format!("{{ {field}({argument}: \"not-an-owner\"){selection} }}"),
+        );
+        assert_eq!(
+            response.errors.len(),
+            1,
+            "{field} accepted a malformed owner"
+        );
+    }
+
+    // Profile lookups treat a malformed owner as unknown
+    let data = query_data(
+        &service,
+        "{ agent(owner: \"not-an-owner\") { name } \
+           client(owner: \"not-an-owner\") { owner } }",
+    );
+    assert_eq!(data, json!({ "agent": null, "client": null }));
+}
+
+#[test]
+fn ban_and_status_lookups_read_the_state() {
+    let banned = owner("banned");
+    let service = create_service(|state| {
+        state.banned_mut().insert(&banned, true).unwrap();
+        state
+            .status_counts_mut()
+            .insert(&JobStatus::Posted, 2)
+            .unwrap();
+        state
+            .status_counts_mut()
+            .insert(&JobStatus::Completed, 1)
+            .unwrap();
+    });
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ banned: isBanned(owner: \"{banned}\") other: isBanned(owner: \"{}\") \
+             all: jobsCount posted: jobsCount(status: POSTED) }}",
+            owner("other")
+        ),
+    );
+
+    assert_eq!(
+        data,
+        json!({ "banned": true, "other": false, "all": 3, "posted": 2 })
+    );
+}
+
+#[test]
+fn category_lookups_scan_every_stored_job() {
+    let client = owner("client");
+    let service = create_service(|state| {
+        let mut jobs: Vec<Job> = (1..=3).map(|id| sample_job(id, client, NOW_SECS)).collect();
+        jobs[1].category = JobCategory::DataAnalysis;
+        insert_jobs(state, jobs);
+        insert_agents(state, vec![sample_agent(owner("agent"), "Agent")]);
+    });
+
+    let data = query_data(
+        &service,
+        "{ models: jobsByCategory(category: AI_MODEL) { id } \
+           analysis: jobsByCategory(category: DATA_ANALYSIS) { id } \
+           agentsCount }",
+    );
+
+    assert_eq!(ids(&data["models"]), vec![1, 3]);
+    assert_eq!(ids(&data["analysis"]), vec![2]);
+    assert_eq!(data["agentsCount"], json!(1));
+}
+
+// ==================== PAGINATION ====================
+
+#[test]
+fn jobs_after_pages_through_every_job() {
+    let client = owner("client");
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            (1..=5).map(|id| sample_job(id, client, NOW_SECS)).collect(),
+        );
+    });
+
+    let mut pages = Vec::new();
+    let mut cursor = "null".to_string();
+    loop {
+        let data = query_data(
+            &service,
+            &format!("{{ jobsAfter(cursor: {cursor}, limit: 2) {{ items {{ id }} nextCursor }} }}"),
+        );
+        let page = &data["jobsAfter"];
+        pages.push(ids(&page["items"]));
+        match page["nextCursor"].as_u64() {
+            Some(next) => cursor = next.to_string(),
+            None => break,
+        }
+    }
+
+    assert_eq!(pages, vec![vec![1, 2], vec![3, 4], vec![5]]);
+}
+
+#[test]
+fn page_totals_count_every_match_across_pages() {
+    let client = owner("client");
+    let service = create_service(|state| {
+        let mut jobs: Vec<Job> = (1..=5).map(|id| sample_job(id, client, NOW_SECS)).collect();
+        jobs[4].category = JobCategory::DataAnalysis;
+        insert_jobs(state, jobs);
+        insert_agents(
+            state,
+            (1..=3u64)
+                .map(|n| AgentProfile {
+                    jobs_completed: n,
+                    ..sample_agent(owner(&format!("agent-{n}")), &format!("Agent {n}"))
+                })
+                .collect(),
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ jobs: jobsPage(filter: { category: AI_MODEL }, sortBy: ID, limit: 2, offset: 1) \
+           { total offset items { id } } \
+         pastEnd: jobsPage(limit: 2, offset: 10) { total offset items { id } } \
+         agents: agentsPage(sortBy: JOBS_COMPLETED, sortDir: DESC, limit: 2) \
+           { total offset items { name } } }",
+    );
+
+    assert_eq!(
+        data,
+        json!({
+            "jobs": { "total": 4, "offset": 1, "items": [{ "id": 2 }, { "id": 3 }] },
+            "pastEnd": { "total": 5, "offset": 10, "items": [] },
+            "agents": {
+                "total": 3,
+                "offset": 0,
+                "items": [{ "name": "Agent 3" }, { "name": "Agent 2" }],
+            },
+        })
+    );
+}
+
+// ==================== BIDS ====================
+
+#[test]
+fn bid_summary_aggregates_the_bids_on_a_job() {
+    let client = owner("client");
+    let bid = |bid_id: u64, agent: AccountOwner, amount: u128| Bid {
+        agent,
+        bid_id,
+        timestamp: at_secs(NOW_SECS - 100 + bid_id),
+        amount: tokens(amount),
+        proposal: "I can do it".to_string(),
+        estimated_days: 3,
+        stake: Amount::ZERO,
+    };
+    let (first, second, third) = (owner("first"), owner("second"), owner("third"));
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            vec![
+                Job {
+                    bids: vec![bid(0, first, 30), bid(1, second, 45), bid(2, third, 30)],
+                    ..sample_job(1, client, NOW_SECS)
+                },
+                sample_job(2, client, NOW_SECS),
+            ],
+        );
+    });
+
+    let fields = "count minAmount maxAmount avgAmount lowestBidder";
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ bids: jobBidSummary(jobId: 1) {{ {fields} }} \
+             empty: jobBidSummary(jobId: 2) {{ {fields} }} \
+             missing: jobBidSummary(jobId: 3) {{ count }} }}"
+        ),
+    );
+
+    // Ties on the lowest amount go to the earliest bid
+    assert_eq!(
+        data,
+        json!({
+            "bids": {
+                "count": 3,
+                "minAmount": tokens(30).to_string(),
+                "maxAmount": tokens(45).to_string(),
+                "avgAmount": tokens(35).to_string(),
+                "lowestBidder": first.to_string(),
+            },
+            "empty": {
+                "count": 0,
+                "minAmount": Amount::ZERO.to_string(),
+                "maxAmount": Amount::ZERO.to_string(),
+                "avgAmount": Amount::ZERO.to_string(),
+                "lowestBidder": null,
+            },
+            "missing": null,
+        })
+    );
+}
+
+// ==================== JOB FILTERS ====================
+
+#[test]
+fn expired_jobs_can_be_excluded() {
+    let client = owner("client");
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            vec![
+                sample_job(1, client, NOW_SECS),
+                Job {
+                    deadline: Some(at_secs(NOW_SECS - 1)),
+                    ..sample_job(2, client, NOW_SECS)
+                },
+                Job {
+                    status: JobStatus::Expired,
+                    ..sample_job(3, client, NOW_SECS)
+                },
+                Job {
+                    deadline: Some(at_secs(NOW_SECS + 1)),
+                    ..sample_job(4, client, NOW_SECS)
+                },
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ all: jobs(sortBy: ID) { id } \
+         current: jobs(filter: { excludeExpired: true }, sortBy: ID) { id } }",
+    );
+
+    assert_eq!(ids(&data["all"]), vec![1, 2, 3, 4]);
+    assert_eq!(ids(&data["current"]), vec![1, 4]);
+}
+
+#[test]
+fn jobs_are_filtered_by_category_and_tags() {
+    let client = owner("client");
+    let tagged_job = |id: u64, category: JobCategory, tags: &[&str]| Job {
+        category,
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..sample_job(id, client, NOW_SECS)
+    };
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            vec![
+                tagged_job(1, JobCategory::AIModel, &["rust", "wasm"]),
+                tagged_job(2, JobCategory::AIModel, &["python"]),
+                tagged_job(3, JobCategory::DataAnalysis, &["rust"]),
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ any: jobs(filter: { tags: [\"rust\", \"python\"] }, sortBy: ID) { id } \
+         all: jobs(filter: { tags: [\"rust\", \"wasm\"], matchAllTags: true }, sortBy: ID) { id } \
+         combined: jobs(filter: { tags: [\"rust\"], category: DATA_ANALYSIS }) { id } }",
+    );
+
+    assert_eq!(ids(&data["any"]), vec![1, 2, 3]);
+    assert_eq!(ids(&data["all"]), vec![1]);
+    assert_eq!(ids(&data["combined"]), vec![3]);
+}
+
+#[test]
+fn payment_filters_compare_exact_amounts() {
+    let client = owner("client");
+    // One atto apart, far below what an f64 can tell apart at this size
+    let priced_job = |id: u64, attos: u128| Job {
+        payment: Amount::from_attos(attos),
+        ..sample_job(id, client, NOW_SECS)
+    };
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            vec![
+                priced_job(1, 100_000_000_000_000_000_000),
+                priced_job(2, 100_000_000_000_000_000_001),
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ above: jobs(filter: { minPayment: \"100.000000000000000001\" }) { id } \
+         below: jobs(filter: { maxPayment: \"100\" }) { id } }",
+    );
+
+    assert_eq!(ids(&data["above"]), vec![2]);
+    assert_eq!(ids(&data["below"]), vec![1]);
+}
+
+#[test]
+fn jobs_are_filtered_by_creation_time() {
+    let client = owner("client");
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            vec![
+                sample_job(1, client, NOW_SECS - 7200),
+                sample_job(2, client, NOW_SECS - 3600),
+                Job {
+                    status: JobStatus::Cancelled,
+                    ..sample_job(3, client, NOW_SECS - 1800)
+                },
+                sample_job(4, client, NOW_SECS - 60),
+            ],
+        );
+    });
+    let hour_ago = at_secs(NOW_SECS - 3600).micros();
+    let minutes_ago = at_secs(NOW_SECS - 600).micros();
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ recent: jobs(filter: {{ createdAfter: {hour_ago} }}, sortBy: ID) {{ id }} \
+             window: jobs(filter: {{ createdAfter: {hour_ago}, createdBefore: {minutes_ago} }}, sortBy: ID) {{ id }} \
+             posted: jobs(filter: {{ createdAfter: {hour_ago}, status: POSTED }}, sortBy: ID) {{ id }} }}"
+        ),
+    );
+
+    // Both bounds are inclusive
+    assert_eq!(ids(&data["recent"]), vec![2, 3, 4]);
+    assert_eq!(ids(&data["window"]), vec![2, 3]);
+    assert_eq!(ids(&data["posted"]), vec![2, 4]);
+}
+
+// ==================== FAVORITES ====================
+
+#[test]
+fn favorites_are_listed_in_the_order_added() {
+    let (client, user) = (owner("client"), owner("user"));
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            vec![
+                sample_job(1, client, NOW_SECS),
+                sample_job(2, client, NOW_SECS),
+            ],
+        );
+        // Job 7 was bookmarked and later removed
+        state.favorites_mut().insert(&user, vec![2, 7, 1]).unwrap();
+    });
+
+    let data = query_data(
+        &service,
+        &format!(
+            "{{ mine: myFavorites(owner: \"{user}\") {{ id }} \
+             none: myFavorites(owner: \"{client}\") {{ id }} }}"
+        ),
+    );
+
+    assert_eq!(ids(&data["mine"]), vec![2, 1]);
+    assert_eq!(ids(&data["none"]), Vec::<u64>::new());
+}
+
+// ==================== SEARCH ====================
+
+#[test]
+fn search_matches_descriptions_and_tags_newest_first() {
+    let client = owner("client");
+    let service = create_service(|state| {
+        insert_jobs(
+            state,
+            vec![
+                Job {
+                    description: "Label a Vision dataset".to_string(),
+                    ..sample_job(1, client, NOW_SECS - 20)
+                },
+                Job {
+                    description: "Summarize reports".to_string(),
+                    ..sample_job(2, client, NOW_SECS - 10)
+                },
+                Job {
+                    description: "Train a detector".to_string(),
+                    tags: vec!["computer-vision".to_string()],
+                    ..sample_job(3, client, NOW_SECS)
+                },
+            ],
+        );
+    });
+
+    let data = query_data(
+        &service,
+        "{ all: searchJobs(query: \"VISION\") { id } \
+         first: searchJobs(query: \"vision\", limit: 1) { id } }",
+    );
+
+    assert_eq!(ids(&data["all"]), vec![3, 1]);
+    assert_eq!(ids(&data["first"]), vec![3]);
+}
+
+// ==================== STATISTICS ====================
+
+#[test]
+fn stats_report_average_job_payment() {
+    let service = create_service(|state| {
+        state.next_job_id_mut().set(5);
+        state.total_payment_volume_mut().set(tokens(100));
+    });
+
+    let data = query_data(
+        &service,
+        "{ stats { totalPaymentVolume averageJobPayment } }",
+    );
+
+    assert_eq!(
+        data,
+        json!({ "stats": { "totalPaymentVolume": tokens(100).to_string(), "averageJobPayment": "25." } })
+    );
+}
+
+#[test]
+fn stats_fail_once_payment_volume_overflows() {
+    let service = create_service(|state| {
+        state.next_job_id_mut().set(3);
+        state.total_payment_volume_mut().set(Amount::MAX);
+        state.payment_volume_overflowed_mut().set(true);
+    });
+
+    let response = query(&service, "{ stats { averageJobPayment } }");
+
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(
+        response.errors[0].message,
+        "Total payment volume overflows Amount"
+    );
+}
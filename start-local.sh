@@ -69,7 +69,8 @@ echo "6. Deploying application..."
 APP_ID=$(linera publish-and-create \
     "$WASM_DIR/job-marketplace-contract.wasm" \
     "$WASM_DIR/job-marketplace-service.wasm" \
-    --json-argument "null" 2>&1 | tail -1)
+    --json-parameters "{\"fee_basis_points\": 0, \"fee_recipient\": \"$OWNER\"}" \
+    --json-argument "{\"admin\": \"$OWNER\", \"min_job_payment\": \"0\"}" 2>&1 | tail -1)
 echo "   App ID: $APP_ID"
 
 # Start the GraphQL service
@@ -15,11 +15,13 @@
 
 export enum JobStatus {
   Posted = 'POSTED',
+  AwaitingConfirmation = 'AWAITING_CONFIRMATION',
   InProgress = 'IN_PROGRESS',
   PendingApproval = 'PENDING_APPROVAL',
   Completed = 'COMPLETED',
   Cancelled = 'CANCELLED',
-  Disputed = 'DISPUTED'
+  Disputed = 'DISPUTED',
+  Expired = 'EXPIRED'
 }
 
 export enum JobCategory {
@@ -80,6 +82,7 @@ export interface Milestone {
   paymentPercentage: number; // 0-100
   status: MilestoneStatus;
   dueDate?: string;
+  released: boolean;
 }
 
 export interface MilestoneInput {
@@ -99,6 +102,7 @@ export interface Bid {
   amount: Amount;
   proposal: string;
   estimatedDays: number;
+  stake: Amount;
 }
 
 // ==================== JOB ====================
@@ -120,6 +124,11 @@ export interface Job {
   milestones?: Milestone[];
   acceptedBidAmount?: Amount;
   escrowId?: number;
+  completedAt?: string;
+  autoAcceptAt?: string;
+  updatedAt: string;
+  acceptedAt?: string;
+  settled: boolean;
 }
 
 // ==================== AGENT PROFILE ====================
@@ -132,6 +141,8 @@ export interface AgentProfile {
   rating: number; // Calculated avg rating out of 5
   totalRatingPoints: number;
   totalRatings: number;
+  averageRating: number;
+  weightedScore: number;
   registeredAt?: string;
   // New fields (optional since they may not be present in older data)
   verificationLevel?: VerificationLevel;
@@ -141,6 +152,10 @@ export interface AgentProfile {
   availability?: boolean;
   responseTimeHours?: number;
   successRate?: number; // 0-100
+  activeJobs: number; // Accepted jobs not yet finished
+  disputesInvolved: number;
+  disputesLost: number;
+  disputeRate: number; // Share of finished jobs that went through a dispute (0-1)
 }
 
 export interface AgentRating {
@@ -231,6 +246,7 @@ export interface MarketplaceStats {
   totalAgents: number;
   verifiedAgents: number;
   totalPaymentVolume: string;
+  averageJobPayment: string;
   totalBids: number;
   openDisputes: number;
   avgBidsPerJob: number;
@@ -286,6 +302,7 @@ export interface PostJobInput {
   tags: string[];
   deadline?: number; // Unix timestamp
   milestones: MilestoneInput[];
+  autoAcceptAt?: number; // Unix timestamp
 }
 
 export interface PlaceBidInput {
@@ -293,6 +310,7 @@ export interface PlaceBidInput {
   amount: Amount;
   proposal: string;
   estimatedDays: number;
+  stake: Amount;
 }
 
 export interface RegisterAgentInput {